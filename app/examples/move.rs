@@ -149,41 +149,35 @@ impl Field {
         Ok(())
     }
 
-    pub fn puck_bounces_side(&self, puck_pos: &cv::Point) -> Option<(cv::Point, cv::Point)> {
-        if puck_pos.x() < self.lt.x() {
-            Some((self.lt, self.lb))
-        } else if puck_pos.x() > self.rb.x() {
-            Some((self.rt, self.rb))
-        } else {
-            None
-        }
+    /// Left and right side wall.
+    pub fn sides(&self) -> [cv::Segment; 2] {
+        [
+            cv::Segment::from_points(&self.lt, &self.lb),
+            cv::Segment::from_points(&self.rt, &self.rb),
+        ]
     }
 }
 
 fn predict(s: &cv::Point, e: &cv::Point, y: i32, field: &Field) -> (cv::Point, Option<cv::Point>) {
-    if e.x() == s.x() {
-        return (cv::Point::new(e.x(), y), None);
-    }
-
-    let m = (e.y() - s.y()) as f32 / (e.x() - s.x()) as f32;
-    let x = ((y - e.y()) as f32 / m + e.x() as f32) as i32;
-
-    let point = cv::Point::new(x, y);
+    let goal = cv::Segment::from_points(&cv::Point::new(field.lb.x(), y), &cv::Point::new(field.rb.x(), y));
+    let ray = cv::Ray::through(&cv::Vec2::from(s), &cv::Vec2::from(e));
+    let goal_hit = ray.intersection(&goal);
 
-    if let Some((l_start, l_end)) = field.puck_bounces_side(&point) {
-        let m1 = (e.y() - s.y()) as f32 / (e.x() - s.x()) as f32;
-        let m2 = (l_end.y() - l_start.y()) as f32 / (l_end.x() - l_start.x()) as f32;
-
-        let b1 = s.y() as f32 - s.x() as f32 * m1;
-        let b2 = l_start.y() as f32 - l_start.x() as f32 * m2;
-
-        let x = (b2 - b1) / (m1 - m2);
-        let y_ = (m1 * x + b1) as i32;
-        let bp = cv::Point::new(x as i32, y_ as i32);
-        let point = cv::Point::new(((y - bp.y()) as f32 / (-m) + bp.x() as f32) as i32, y);
-        (point, Some(bp))
-    } else {
-        (point, None)
+    // first side wall crossed before the goal line
+    let bounce = field
+        .sides()
+        .iter()
+        .filter_map(|side| ray.intersection(side).map(|(t, p)| (t, p, *side)))
+        .filter(|(t, _, _)| goal_hit.map_or(true, |(goal_t, _)| *t < goal_t))
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    match bounce {
+        Some((_, bp, side)) => {
+            let ray = cv::Ray::new(bp, side.reflect(&ray.dir()));
+            let point = ray.intersection(&goal).map_or(bp, |(_, p)| p);
+            (point.round(), Some(bp.round()))
+        }
+        None => (goal_hit.map_or(*e, |(_, p)| p.round()), None),
     }
 }
 
@@ -0,0 +1,65 @@
+//!
+//! compares the detection pipelines (see `Pipeline`) on the same input
+//!
+//! ```cargo run --release --example pipeline-bench -- <video-or-image> [config-file] [frames]```
+//!
+use airhobot::prelude::*;
+use std::{
+    env,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+fn main() -> Result<()> {
+    let usage = "usage: pipeline-bench <video-or-image> [config-file] [frames]";
+    let path = PathBuf::from(env::args().nth(1).ok_or::<Error>(usage.into())?);
+    let cfg = match env::args().nth(2) {
+        Some(cfg) => Config::load(cfg)?,
+        None => Config::default(),
+    };
+    let n = env::args().nth(3).and_then(|n| n.parse().ok()).unwrap_or(300);
+
+    // read the frames upfront - the bench measures only the processing
+    let source = match cv::imread(&path) {
+        Ok(image) if !image.is_empty()? => Source::image(&path)?,
+        _ => Source::video(&path)?.with_looping(true),
+    };
+    let frames: Vec<Frame> = source.take(n).collect();
+    if frames.is_empty() {
+        return Err("empty source".into());
+    }
+
+    let cfg = Arc::new(RwLock::new(cfg));
+    for pipeline in &[Pipeline::Warp, Pipeline::Raw, Pipeline::RawMasked] {
+        cfg.write()?.pipeline = *pipeline;
+        let (elapsed, detections) = run(&cfg, &frames)?;
+        let per_frame = elapsed / frames.len() as u32;
+        println!(
+            "{:?}: {} frames in {:?} - {:?} per frame ({:.1} fps) - puck found in {} frames",
+            pipeline,
+            frames.len(),
+            elapsed,
+            per_frame,
+            1.0 / per_frame.as_secs_f64(),
+            detections
+        );
+    }
+    Ok(())
+}
+
+fn run(cfg: &Cfg, frames: &[Frame]) -> Result<(Duration, usize)> {
+    let mut state = State::new(Arc::clone(cfg), frames[0].clone());
+    let mut detections = 0;
+    let started = Instant::now();
+    for frame in frames {
+        state = state.next(frame.clone());
+        state.crop_frame()?;
+        state.apply_filter_frame()?;
+        state.detect_objects()?;
+        if state.puck.is_some() {
+            detections += 1;
+        }
+    }
+    Ok((started.elapsed(), detections))
+}
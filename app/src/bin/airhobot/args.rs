@@ -6,17 +6,30 @@ use structopt::StructOpt;
 /// KEYBOARD SHORTCUTS:
 ///
 ///   1: select field
-///   2: pick pusher color
-///   3: pick puck color
+///   2: pick pusher color (drag a region, or click some points and press enter)
+///   3: pick puck color (drag a region, or click some points and press enter)
 ///   4: simulate puck (place two points in the field)
 ///   5: move pusher
+///   t: capture the pusher template (drag a region around the pusher)
 ///   c: show controls
 ///   r: reload config
 ///   f: next frame
 ///   s: save state
+///   d: save a clip of the last seconds
+///   b: freeze / unfreeze the background model (motion detection)
 ///   q: quit
 ///
 ///   use the spacebar for pause
+///
+/// PLAYBACK (videos and image sequences):
+///
+///   ,: previous frame
+///   [: seek 5 seconds back
+///   ]: seek 5 seconds forward
+///   0: restart
+///   +: double the playback speed
+///   -: half the playback speed
+///   l: toggle looping
 #[derive(StructOpt, Debug)]
 #[structopt(name = "AirHoBot", verbatim_doc_comment)]
 pub struct Args {
@@ -43,16 +56,106 @@ pub struct Args {
     /// Use the video as input source
     #[structopt(long, conflicts_with = "image, cam_id")]
     pub video: Option<PathBuf>,
+
+    /// Use the numbered images (PNG / JPEG) in the directory as input source
+    #[structopt(long, conflicts_with_all = &["cam", "image", "video"])]
+    pub images: Option<PathBuf>,
+
+    /// Use the network stream (MJPEG over http:// or rtsp://) as input source
+    #[structopt(long, conflicts_with_all = &["cam", "image", "video", "images"])]
+    pub url: Option<String>,
+
+    /// Use the GStreamer pipeline (ending with an `appsink`) as input source
+    #[structopt(long, conflicts_with_all = &["cam", "image", "video", "images", "url"])]
+    pub gstreamer: Option<String>,
+
+    /// Use a recording (directory from `--record`) as input source
+    #[structopt(long, conflicts_with_all = &["cam", "image", "video", "images", "url", "gstreamer"])]
+    pub replay: Option<PathBuf>,
+
+    /// Record the raw and annotated frames with the detections into the directory
+    #[structopt(long)]
+    pub record: Option<PathBuf>,
+
+    /// Video codec for the recording (four character code like XVID, MJPG, mp4v)
+    #[structopt(long, default_value = "XVID")]
+    pub codec: cv::FourCC,
+
+    /// Frame rate of the recorded videos
+    #[structopt(long, default_value = "30")]
+    pub record_fps: f64,
+
+    /// Start from the beginning when the end of the input source is reached
+    #[structopt(long = "loop")]
+    pub looping: bool,
+
+    /// Capture width (overrides the config value)
+    #[structopt(long)]
+    pub width: Option<i32>,
+
+    /// Capture height (overrides the config value)
+    #[structopt(long)]
+    pub height: Option<i32>,
+
+    /// Capture fps (overrides the config value)
+    #[structopt(long)]
+    pub fps: Option<f64>,
+
+    /// Capture exposure - disables auto exposure (overrides the config value)
+    #[structopt(long)]
+    pub exposure: Option<f64>,
+
+    /// Capture gain (overrides the config value)
+    #[structopt(long)]
+    pub gain: Option<f64>,
+
+    /// Capture white balance in kelvin - disables auto white balance (overrides the config value)
+    #[structopt(long)]
+    pub white_balance: Option<f64>,
+
+    /// Capture buffer size in frames (overrides the config value)
+    #[structopt(long)]
+    pub buffer_size: Option<i32>,
 }
 
 impl Args {
-    pub fn source(&self) -> Result<Source> {
+    /// the capture settings from the config, overridden by the command line arguments
+    pub fn capture_settings(&self, cfg: &cv::CaptureSettings) -> cv::CaptureSettings {
+        cfg.merge(&cv::CaptureSettings {
+            width: self.width,
+            height: self.height,
+            fps: self.fps,
+            exposure: self.exposure,
+            auto_exposure: self.exposure.map(|_| false),
+            gain: self.gain,
+            white_balance: self.white_balance,
+            auto_white_balance: self.white_balance.map(|_| false),
+            buffer_size: self.buffer_size,
+        })
+    }
+
+    pub fn recorder(&self) -> Result<Option<Recorder>> {
+        self.record
+            .as_ref()
+            .map(|dir| Recorder::create(dir, self.codec, self.record_fps))
+            .transpose()
+    }
+
+    pub fn source(&self, capture_settings: &cv::CaptureSettings) -> Result<Source> {
         self.cam
-            .map(Source::cam)
+            .map(|device_id| Source::cam_with_settings(device_id, capture_settings))
             .or(self.video.as_ref().map(Source::video))
             .or(self.image.as_ref().map(Source::image))
+            .or(self
+                .images
+                .as_ref()
+                .map(|dir| Source::image_sequence(dir, self.looping)))
+            .or(self.url.as_ref().map(|url| Source::url(url)))
+            .or(self.gstreamer.as_ref().map(|pipeline| Source::gstreamer(pipeline)))
+            .or(self.replay.as_ref().map(Source::recording))
             .unwrap_or(Err(Error::Arguments {
                 msg: "input source missing - use the `-h` flag for help".into(),
             }))
+            .map(|source| source.with_looping(self.looping))
     }
 }
@@ -22,11 +22,27 @@ macro_rules! bind_values {
     }
 }
 
-pub struct ControlPanel(Option<cv::GUI>, Arc<RwLock<Config>>);
+/// `.2`: window with the color masks - see `ControlPanel::show_masks`
+pub struct ControlPanel(Option<cv::GUI>, Arc<RwLock<Config>>, Option<cv::GUI>);
 impl ControlPanel {
     pub fn new(cfg: Config) -> (Self, Arc<RwLock<Config>>) {
         let cfg = Arc::new(RwLock::new(cfg));
-        (Self(None, Arc::clone(&cfg)), cfg)
+        (Self(None, Arc::clone(&cfg), None), cfg)
+    }
+
+    /// shows the masks of the color ranges before and after a change side by side.
+    pub fn show_masks(&mut self, frame: &cv::Mat<cv::BGR>, before: &cv::HSVRange, after: &cv::HSVRange) -> Result<()> {
+        let hsv: cv::Mat<cv::HSV> = frame.convert_color();
+        let mask = |range: &cv::HSVRange, label: &str| {
+            let mut mask: cv::Mat<cv::BGR> = hsv.in_range(range).convert_color();
+            mask.draw_text(label, &cv::Point::new(10, 20), 0.6, cv::RGB::red(), 1);
+            mask.draw_text(&range.to_string(), &cv::Point::new(10, 45), 0.5, cv::RGB::red(), 1);
+            mask
+        };
+        let masks = mask(before, "before").hconcat(&mask(after, "after"))?;
+        let gui = self.2.get_or_insert_with(|| cv::GUI::new("AirHoBot - Color Mask"));
+        gui.show(&masks)?;
+        Ok(())
     }
 
     pub fn toggle(&mut self) {
@@ -85,7 +101,7 @@ impl ControlPanel {
     pub fn repaint(&mut self) -> Result<()> {
         if let Some(gui) = &self.0 {
             let cfg = self.1.read().unwrap();
-            let mut frame = cv::Mat::<cv::BGR>::new(350, 600, &cv::CVType::CV8SC3, cv::RGB::white())?;
+            let mut frame = cv::Mat::<cv::BGR>::new(480, 600, cv::RGB::white())?;
 
             frame.draw_text(
                 &format!("Pusher color: {}", cfg.pusher.color),
@@ -127,10 +143,15 @@ impl ControlPanel {
                 "3: pick puck color",
                 "4: simulate puck (place two points in the field)",
                 "5: move pusher",
+                "t: capture pusher template",
                 "c: show controls",
                 "s: save state",
+                "d: save a clip of the last seconds",
+                "b: freeze / unfreeze the background model",
                 "q: quit",
                 "use the spacebar for pause",
+                ", [ ] 0: step back, seek -5s / +5s, restart",
+                "+ - l: playback speed, toggle looping",
             ];
             let y_start = 140;
             for (n, t) in keys.iter().enumerate() {
@@ -1,12 +1,15 @@
 use airhobot::prelude::*;
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
 use snafu::ErrorCompat;
-use std::{net::UdpSocket, time::Duration};
+use std::{net::UdpSocket, path::PathBuf, time::Duration};
 use structopt::StructOpt;
 
 mod args;
 mod control_panel;
 
+/// seek step for the `[` / `]` keys
+const SEEK_STEP: Duration = Duration::from_secs(5);
+
 fn main() {
     match run() {
         Ok(()) => (),
@@ -29,54 +32,117 @@ fn run() -> Result<()> {
         Config::default()
     });
 
+    let mut source = args
+        .source(&args.capture_settings(&cfg.capture))?
+        .with_retry_policy(cfg.reconnect.clone());
+
     // initialize the control panel
     let (mut control_panel, cfg) = control_panel::ControlPanel::new(cfg);
 
     let gui = cv::GUI::new("AirHoBot");
     let mut state = {
-        let frame = args.source()?.grab().ok_or::<Error>("empty source".into())?;
+        let frame = source.grab().ok_or::<Error>("empty source".into())?;
         State::new(cfg, frame)
     };
 
+    let mut recorder = args.recorder()?;
+    let mut clips = ClipBuffer::new();
+    let mut markers = MarkerTracker::new();
     let mut pause = false;
-    for frame in args.source()? {
+    while let Some(frame) = source.grab() {
+        if frame.seq > 0 && frame.seq % 100 == 0 {
+            debug!("frame {} - {} frames dropped", frame.seq, source.dropped_frames());
+        }
         state = state.next(frame);
 
+        if let Some(roi) = markers.check(&state)? {
+            state.cfg.write()?.roi = roi;
+        }
+
         state.crop_frame()?;
         state.apply_filter_frame()?;
 
-        let frame_hsv = state.frame.convert_color();
-        state.puck = {
-            let puck_contours = detect("puck", &state.cfg.read()?.puck, &frame_hsv)?;
-            state.frame.draw_contours(&puck_contours, cv::RGB::white(), 2);
-            let puck = puck_contours.iter().next().map(|c| c.center());
-            puck
-        };
-
-        state.pusher = {
-            let pusher_contours = detect("pusher", &state.cfg.read()?.pusher, &frame_hsv)?;
-            state.frame.draw_contours(&pusher_contours, cv::RGB::red(), 2);
-            let pusher = pusher_contours.iter().next().map(|c| c.center());
-            pusher
-        };
+        state.detect_objects()?;
+
+        draw_playback_info(&mut state.frame, &state.frame_orig, &source);
+
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(&state, source.frame_index())?;
+        }
+
+        let event = clips.push(&state, &state.cfg.read()?.clip);
+        if let Some(event) = event {
+            save_clip(&mut clips, event, &state)?;
+        }
 
         loop {
             control_panel.repaint()?;
-            match gui.show_for(&state.frame, Duration::from_millis(args.delay))? {
+            let delay = Duration::from_millis(args.delay).div_f64(source.speed());
+            match gui.show_for(&state.frame, delay)? {
                 '1' => state.cfg.write()?.roi = select_field(&state, &gui)?,
-                '2' => state.cfg.write()?.pusher.color = pick_color(&state, &gui)?,
-                '3' => state.cfg.write()?.puck.color = pick_color(&state, &gui)?,
+                '2' => {
+                    if let Some(stats) = pick_color(&state, &gui)? {
+                        let mut cfg = state.cfg.write()?;
+                        let before = cfg.pusher.color_range()?;
+                        cfg.pusher.fit(&stats);
+                        control_panel.show_masks(state.plain_frame(), &before, &cfg.pusher.color_range()?)?;
+                    }
+                }
+                '3' => {
+                    if let Some(stats) = pick_color(&state, &gui)? {
+                        let mut cfg = state.cfg.write()?;
+                        let before = cfg.puck.color_range()?;
+                        cfg.puck.fit(&stats);
+                        control_panel.show_masks(state.plain_frame(), &before, &cfg.puck.color_range()?)?;
+                    }
+                }
                 '4' => simulate_puck(&state, &gui)?,
                 '5' => move_pusher(&state, &gui)?,
+                't' => {
+                    let path = args.config_file.with_file_name("pusher-template.png");
+                    if let Some(template) = capture_template(&state, &gui, path)? {
+                        state.forget_template(&template);
+                        state.cfg.write()?.pusher.method = DetectionMethod::Template(template);
+                    }
+                }
                 'c' => control_panel.toggle(),
                 'f' => break, // next frame
                 'r' => {
-                    *state.cfg.write()? = Config::load(&args.config_file)?;
+                    match Config::load(&args.config_file) {
+                        Ok(cfg) => *state.cfg.write()? = cfg,
+                        Err(err) => error!("unable to reload config: {} - keep the current config", err),
+                    }
                     break; // next frame
                 }
                 's' => state.cfg.read()?.save(&args.config_file)?,
+                'd' => save_clip(&mut clips, ClipEvent::Manual, &state)?,
+                'b' => {
+                    if let Some(background) = state.background.as_mut() {
+                        background.set_frozen(!background.is_frozen());
+                        info!("background model frozen: {}", background.is_frozen());
+                    }
+                }
                 'q' => return Ok(()),
                 ' ' => pause = !pause,
+                ',' => {
+                    playback(source.step_back());
+                    break; // previous frame
+                }
+                '[' => {
+                    playback(source.seek(state.frame_orig.ts.saturating_sub(SEEK_STEP)));
+                    break;
+                }
+                ']' => {
+                    playback(source.seek(state.frame_orig.ts + SEEK_STEP));
+                    break;
+                }
+                '0' => {
+                    playback(source.seek_frame(0));
+                    break;
+                }
+                '+' => source.set_speed(source.speed() * 2.0),
+                '-' => source.set_speed(source.speed() / 2.0),
+                'l' => source.set_looping(!source.looping()),
                 _ => (),
             }
 
@@ -88,39 +154,172 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+/// log failed playback controls - e.g. seeking in a live source
+fn playback(result: Result<()>) {
+    if let Err(err) = result {
+        warn!("{}", err);
+    }
+}
+
+/// saves the buffered frames as clip
+fn save_clip(clips: &mut ClipBuffer, event: ClipEvent, state: &State) -> Result<()> {
+    if let Err(err) = clips.save(event, &state.cfg.read()?.clip) {
+        warn!("unable to save clip: {}", err);
+    }
+    Ok(())
+}
+
+/// draws the frame counter / timecode overlay
+fn draw_playback_info(frame: &mut cv::Mat<cv::BGR>, orig: &Frame, source: &Source) {
+    let secs = orig.ts.as_secs();
+    let timecode = format!("{:02}:{:02}.{:03}", secs / 60, secs % 60, orig.ts.subsec_millis());
+    let counter = match (source.frame_index(), source.frame_count()) {
+        (Some(n), Some(count)) => format!("frame {} / {}", n, count),
+        _ => format!("frame {}", orig.seq),
+    };
+    let text = format!(
+        "{} - {} - {}x{}",
+        counter,
+        timecode,
+        source.speed(),
+        if source.looping() { " - loop" } else { "" }
+    );
+    let org = cv::Point::new(10, frame.n_rows() - 10);
+    frame.draw_text(&text, &org, 0.5, cv::RGB::white(), 1);
+}
+
 fn select_field(state: &State, gui: &cv::GUI) -> Result<Roi> {
-    info!("Select field - select left-top, right-top, right-bottom, left-bottom");
+    info!("Select field - select the four corners of the table");
     let mouse_events = gui.mouse_events_for::<cv::MouseLeftBtnDown>();
-    let mut frame = state.frame_orig.clone();
+    let mut frame = state.frame_orig.mat.clone();
+
+    // offer the detected table as suggestion
+    let suggestion = cv::detect_table(&frame)?;
+    if let Some(table) = &suggestion {
+        info!(
+            "table detected (shape fit: {:.2}) - press 'a' to accept or select the corners",
+            table.shape_fit
+        );
+        for (i, from) in table.corners.iter().enumerate() {
+            let to = &table.corners[(i + 1) % 4];
+            frame.draw_line(from, to, cv::RGB::white(), 2);
+        }
+        let text = format!("suggestion - shape fit: {:.2} - a: accept", table.shape_fit);
+        frame.draw_text(&text, &cv::Point::new(10, 20), 0.6, cv::RGB::white(), 1);
+    }
+
+    let background = frame.clone();
     let mut vec = Vec::new();
-    while vec.len() < 4 {
+    loop {
         while let Ok(event) = mouse_events.try_recv() {
             frame.draw_circle(&event.point(), 4, cv::RGB::red(), 2);
             vec.push(event.point());
         }
-        gui.show_for(&frame, Duration::from_millis(10))?;
+        match (gui.show_for(&frame, Duration::from_millis(10))?, &suggestion) {
+            ('a', Some(table)) if vec.is_empty() => return Roi::from_corners(table.corners),
+            _ => (),
+        }
+        if vec.len() == 4 {
+            // the corners can be selected in any order
+            match Roi::from_vec(vec.split_off(0)) {
+                Ok(roi) => return Ok(roi),
+                Err(err) => {
+                    warn!("{} - select the corners again", err);
+                    frame = background.clone();
+                }
+            }
+        }
     }
-    Roi::from_vec(vec)
 }
 
-fn pick_color(state: &State, gui: &cv::GUI) -> Result<cv::HSV> {
-    info!("Pick color");
-    let mouse_events = gui.mouse_events_for::<cv::MouseLeftBtnDown>();
+/// saves the dragged region of the working frame as pusher template.
+fn capture_template(state: &State, gui: &cv::GUI, path: PathBuf) -> Result<Option<Template>> {
+    info!("Capture pusher template - drag a region around the pusher (esc: cancel)");
+    let mouse_events = gui.mouse_events();
     let mut frame = state.frame.clone();
+    let mut drag_start = None;
     loop {
         while let Ok(event) = mouse_events.try_recv() {
-            frame.draw_rect(&cv::Rect::center(&event.point(), 10, 10), cv::RGB::red(), 2);
-            gui.show_for(&frame, Duration::from_millis(1000))?;
-            return Ok(frame.at_avg(&event.point(), 3)?);
+            match event {
+                cv::MouseEvent::LeftBtnDown(p) => drag_start = Some(p),
+                cv::MouseEvent::LeftBtnUp(p) => match drag_start.take() {
+                    Some(start) if start.dist(&p) > 3.0 => {
+                        // the template is cut from the frame without the outlines of the detections
+                        let plain = state.plain_frame();
+                        let bounds = cv::Rect::new(0, 0, plain.n_cols(), plain.n_rows());
+                        let region = match cv::Rect::from_corners(&start, &p).intersection(&bounds) {
+                            Some(region) => region,
+                            None => {
+                                warn!("the dragged region is outside of the frame - drag again");
+                                continue;
+                            }
+                        };
+                        cv::imwrite(&path, &plain.roi(region)?)?;
+                        info!("pusher template saved to {}", path.display());
+                        frame.draw_rect(&region, cv::RGB::red(), 2);
+                        gui.show_for(&frame, Duration::from_millis(500))?;
+                        return Ok(Some(Template::new(path)));
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+        if gui.show_for(&frame, Duration::from_millis(10))? == '\u{1b}' {
+            return Ok(None);
+        }
+    }
+}
+
+/// samples the colors in a dragged region or around the clicked points.
+fn pick_color(state: &State, gui: &cv::GUI) -> Result<Option<cv::ColorStats>> {
+    info!("Pick color - drag a region, or click some points and press enter (esc: cancel)");
+    let mouse_events = gui.mouse_events();
+    // the colors are sampled without the outlines of the detections
+    let hsv = state.plain_frame().convert_color();
+    let mut frame = state.frame.clone();
+    let mut points = Vec::new();
+    let mut drag_start = None;
+    loop {
+        while let Ok(event) = mouse_events.try_recv() {
+            match event {
+                cv::MouseEvent::LeftBtnDown(p) => drag_start = Some(p),
+                cv::MouseEvent::LeftBtnUp(p) => match drag_start.take() {
+                    Some(start) if start.dist(&p) > 3.0 => {
+                        let region = cv::Rect::from_corners(&start, &p);
+                        frame.draw_rect(&region, cv::RGB::red(), 2);
+                        gui.show_for(&frame, Duration::from_millis(500))?;
+                        return Ok(log_stats(cv::ColorStats::from_region(&hsv, &region)?));
+                    }
+                    _ => {
+                        frame.draw_circle(&p, 3, cv::RGB::red(), 1);
+                        points.push(p);
+                    }
+                },
+                _ => (),
+            }
+        }
+        match gui.show_for(&frame, Duration::from_millis(10))? {
+            '\r' | '\n' => return Ok(log_stats(cv::ColorStats::from_points(&hsv, &points, 3)?)),
+            '\u{1b}' => return Ok(None),
+            _ => (),
         }
-        gui.show_for(&frame, Duration::from_millis(10))?;
     }
 }
 
+fn log_stats(stats: Option<cv::ColorStats>) -> Option<cv::ColorStats> {
+    match &stats {
+        Some(stats) => info!("color statistics: {:?}", stats),
+        None => warn!("no color samples"),
+    }
+    stats
+}
+
+/// the clicks are in the table view (see `State::table_view`) - the same coordinates as the detections.
 fn simulate_puck(state: &State, gui: &cv::GUI) -> Result<()> {
     info!("Simulate puck - select two points");
     let mouse_events = gui.mouse_events_for::<cv::MouseLeftBtnDown>();
-    let mut frame = state.frame.clone();
+    let mut frame = state.table_view()?;
     let mut points = Vec::new();
     while points.len() < 2 {
         while let Ok(event) = mouse_events.try_recv() {
@@ -131,20 +330,25 @@ fn simulate_puck(state: &State, gui: &cv::GUI) -> Result<()> {
     }
     let (from, to) = (points[0], points[1]);
     frame.draw_line(&from, &to, cv::RGB::white(), 2);
-    let path = predict(&frame, &from, &to);
-    for (i, from) in path.iter().enumerate() {
-        if let Some(to) = path.get(i + 1) {
-            frame.draw_line(from, to, cv::RGB::red(), 2);
+    match predict(&frame, &from, &to, state.cfg.read()?.puck.radius) {
+        Some(path) => {
+            for (i, from) in path.iter().enumerate() {
+                if let Some(to) = path.get(i + 1) {
+                    frame.draw_line(from, to, cv::RGB::red(), 2);
+                }
+            }
         }
+        None => warn!("the puck doesn't reach the goal line"),
     }
     gui.show_for(&frame, Duration::from_millis(2000))?;
     Ok(())
 }
 
+/// the target is selected in the table view (see `State::table_view`) - like the pusher position.
 fn move_pusher(state: &State, gui: &cv::GUI) -> Result<()> {
     info!("Move pusher - select a target point");
     let mouse_events = gui.mouse_events_for::<cv::MouseLeftBtnDown>();
-    let mut frame = state.frame.clone();
+    let mut frame = state.table_view()?;
     loop {
         while let Ok(event) = mouse_events.try_recv() {
             let target = event.point();
@@ -0,0 +1,119 @@
+use airhobot::prelude::*;
+use log::{error, info, warn};
+use std::{path::PathBuf, time::Duration};
+use structopt::StructOpt;
+
+/// Camera calibration with a chessboard.
+///
+/// Hold a printed chessboard in front of the camera - from different
+/// angles and distances, and near the image edges. The detected corners
+/// are drawn in the preview.
+///
+/// KEYBOARD SHORTCUTS:
+///
+///   space: use the current frame (if the chessboard was found)
+///   c: calibrate and save the calibration in the config file
+///   u: toggle the undistorted preview
+///   q: quit
+#[derive(StructOpt, Debug)]
+#[structopt(name = "calibrate", verbatim_doc_comment)]
+struct Args {
+    /// config file
+    #[structopt(short, long, default_value = "airhobot.toml")]
+    config_file: PathBuf,
+
+    /// inner corners per chessboard row
+    #[structopt(long, default_value = "9")]
+    cols: i32,
+
+    /// inner corners per chessboard column
+    #[structopt(long, default_value = "6")]
+    rows: i32,
+
+    /// edge length of a chessboard square in millimeters
+    #[structopt(long, default_value = "25")]
+    square_size: f32,
+
+    /// Use the cam with the given id as input source
+    #[structopt(long)]
+    cam: Option<i32>,
+
+    /// Use the video as input source
+    #[structopt(long, conflicts_with = "cam")]
+    video: Option<PathBuf>,
+
+    /// Use the numbered images (PNG / JPEG) in the directory as input source
+    #[structopt(long, conflicts_with_all = &["cam", "video"])]
+    images: Option<PathBuf>,
+}
+
+fn main() {
+    env_logger::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    if let Err(e) = run(Args::from_args()) {
+        eprintln!("Error: {}", e);
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut cfg = Config::load(&args.config_file).unwrap_or_else(|e| {
+        error!("unable to load config: {} - start with default config", e);
+        Config::default()
+    });
+
+    let source = if let Some(device_id) = args.cam {
+        Source::cam_with_settings(device_id, &cfg.capture)?
+    } else if let Some(video) = &args.video {
+        Source::video(video)?
+    } else if let Some(dir) = &args.images {
+        Source::image_sequence(dir, false)?
+    } else {
+        return Err(Error::Arguments {
+            msg: "input source missing - use the `-h` flag for help".into(),
+        });
+    };
+
+    let gui = cv::GUI::new("AirHoBot - Calibration");
+    let mut calibrator = cv::ChessboardCalibrator::new(args.cols, args.rows, args.square_size);
+    let mut undistorted = false;
+    for frame in source {
+        let view = calibrator.find_view(&frame.mat)?;
+
+        let mut preview = match (&cfg.calibration, undistorted) {
+            (Some(calibration), true) => calibration.undistort(&frame.mat)?,
+            _ => frame.mat.clone(),
+        };
+        for p in view.iter().flat_map(cv::ChessboardView::points) {
+            preview.draw_circle(&p, 4, cv::RGB::red(), 2);
+        }
+        let text = format!(
+            "{} views - space: use frame, c: calibrate, u: undistort ({}), q: quit",
+            calibrator.len(),
+            if undistorted { "on" } else { "off" }
+        );
+        preview.draw_text(&text, &cv::Point::new(10, 20), 0.5, cv::RGB::white(), 1);
+
+        match gui.show_for(&preview, Duration::from_millis(30))? {
+            ' ' => match view {
+                // the corners from the preview - no second detection
+                Some(view) => match calibrator.add(view) {
+                    Ok(()) => info!("view {} added", calibrator.len()),
+                    Err(err) => warn!("{}", err),
+                },
+                None => warn!("chessboard not found"),
+            },
+            'c' => match calibrator.calibrate() {
+                Ok(calibration) => {
+                    info!("calibration: {:?}", calibration);
+                    cfg.calibration = Some(calibration);
+                    cfg.save(&args.config_file)?;
+                    undistorted = true;
+                }
+                Err(err) => warn!("{}", err),
+            },
+            'u' => undistorted = !undistorted,
+            'q' => break,
+            _ => (),
+        }
+    }
+    Ok(())
+}
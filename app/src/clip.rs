@@ -0,0 +1,307 @@
+use crate::prelude::*;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt,
+    path::PathBuf,
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Events which save a clip of the last seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClipEvent {
+    /// the puck was lost near the top / bottom edge of the field
+    Goal,
+    /// the puck was lost somewhere else
+    PuckLost,
+    /// the puck moves faster than `Clip::fast_puck_speed`
+    FastPuck,
+    /// hotkey
+    Manual,
+}
+
+impl fmt::Display for ClipEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ClipEvent::Goal => "goal",
+            ClipEvent::PuckLost => "puck-lost",
+            ClipEvent::FastPuck => "fast-puck",
+            ClipEvent::Manual => "manual",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+struct Entry {
+    frame: Frame,
+    record: FrameRecord,
+}
+
+/// Ring buffer with the frames and detections of the last seconds.
+///
+/// When an event fires (see `Clip::events`) or `ClipBuffer::save` is called,
+/// the buffer is written as a recording (see `Recorder`) in a new
+/// subdirectory of `Clip::dir` - so a clip can be replayed with `Source::recording`.
+#[derive(Default)]
+pub struct ClipBuffer {
+    entries: VecDeque<Entry>,
+    /// last seen puck position
+    last_puck: Option<cv::Point>,
+    /// number of frames since the puck was seen
+    lost: usize,
+    /// number of saved clips - keeps the clip directories unique
+    saved: usize,
+    /// background threads which write the saved clips - joined on drop
+    writers: Vec<JoinHandle<()>>,
+}
+
+impl ClipBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// number of buffered frames
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// adds the raw frame and the detections of the state to the ring buffer.
+    ///
+    /// returns the event which fired with this frame.
+    pub fn push(&mut self, state: &State, cfg: &Clip) -> Option<ClipEvent> {
+        let frame = state.frame_orig.clone();
+        if let Some(last) = self.entries.back() {
+            // start over, when the source time went backwards (seek, loop)
+            if frame.since(&last.frame).is_none() {
+                self.entries.clear();
+            }
+        }
+        let keep = Duration::from_secs_f64(cfg.seconds.max(0.0));
+        while let Some(first) = self.entries.front() {
+            let expired = frame.since(&first.frame).map_or(true, |dur| dur > keep);
+            if expired || self.entries.len() >= cfg.max_frames.max(1) {
+                self.entries.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.entries.push_back(Entry {
+            frame,
+            record: FrameRecord::from_state(state),
+        });
+
+        let event = self.detect_event(state, cfg)?;
+        if cfg.events.contains(&event) {
+            Some(event)
+        } else {
+            None
+        }
+    }
+
+    fn detect_event(&mut self, state: &State, cfg: &Clip) -> Option<ClipEvent> {
+        if let Some(puck) = state.puck {
+            self.last_puck = Some(puck);
+            self.lost = 0;
+            return Self::fast_puck(state, cfg);
+        }
+
+        let last = self.last_puck?;
+        self.lost += 1;
+        if self.lost < cfg.lost_frames.max(1) {
+            return None;
+        }
+        self.last_puck = None;
+        let (_, height) = state.table_size();
+        if last.y() < cfg.goal_margin || last.y() > height - cfg.goal_margin {
+            Some(ClipEvent::Goal)
+        } else {
+            Some(ClipEvent::PuckLost)
+        }
+    }
+
+    /// `ClipEvent::FastPuck` when the puck moved at least `Clip::fast_puck_min_dist` pixels
+    /// with at least `Clip::fast_puck_speed` pixels per millisecond since its last position.
+    fn fast_puck(state: &State, cfg: &Clip) -> Option<ClipEvent> {
+        let (dist, dur) = state.puck_movement()?;
+        let millis = dur.as_secs_f64() * 1000.0;
+        if dist >= cfg.fast_puck_min_dist && millis > 0.0 && dist / millis >= cfg.fast_puck_speed {
+            Some(ClipEvent::FastPuck)
+        } else {
+            None
+        }
+    }
+
+    /// writes the buffered frames in a background thread and empties the buffer.
+    ///
+    /// returns the directory of the clip.
+    pub fn save(&mut self, event: ClipEvent, cfg: &Clip) -> Result<PathBuf> {
+        if self.entries.is_empty() {
+            return Err(Error::Generic {
+                msg: "no frames for the clip".into(),
+            });
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let dir = cfg.dir.join(format!(
+            "{}-{:03}-{}-{}",
+            now.as_secs(),
+            now.subsec_millis(),
+            self.saved,
+            event
+        ));
+        self.saved += 1;
+        info!(
+            "save {} frames for the {} event to {}",
+            self.entries.len(),
+            event,
+            dir.display()
+        );
+
+        let mut recorder = Recorder::create(dir.clone(), cfg.codec, cfg.fps)?;
+        let entries = std::mem::take(&mut self.entries);
+        let writer = thread::spawn(move || {
+            for entry in entries {
+                if let Err(err) = recorder.write(&entry.frame.mat, None, entry.record) {
+                    error!("unable to write the clip: {}", err);
+                    return;
+                }
+            }
+        });
+        self.writers.push(writer);
+        Ok(dir)
+    }
+}
+
+impl Drop for ClipBuffer {
+    /// waits until the saved clips are completely written.
+    fn drop(&mut self) {
+        if !self.writers.is_empty() {
+            info!("wait for {} clip writer(s)", self.writers.len());
+        }
+        for writer in self.writers.drain(..) {
+            if writer.join().is_err() {
+                error!("clip writer panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, RwLock};
+
+    fn clip_cfg() -> Clip {
+        Clip {
+            seconds: 1.0,
+            max_frames: 10,
+            events: vec![ClipEvent::Goal, ClipEvent::PuckLost, ClipEvent::FastPuck],
+            lost_frames: 2,
+            goal_margin: 20,
+            ..Clip::default()
+        }
+    }
+
+    /// feeds the frames with the puck positions every `interval` ms into the buffer - returns the events.
+    fn play(buffer: &mut ClipBuffer, cfg: &Clip, interval: u64, pucks: &[Option<cv::Point>]) -> Vec<ClipEvent> {
+        let config: Cfg = Arc::new(RwLock::new(Config::default()));
+        let mut state = State::new(config, test_frame(0, 0));
+        let mut events = Vec::new();
+        for (i, puck) in pucks.iter().enumerate() {
+            if i > 0 {
+                state = state.next(test_frame(i as u64 * interval, i as u64));
+            }
+            state.puck = *puck;
+            events.extend(buffer.push(&state, cfg));
+        }
+        events
+    }
+
+    #[test]
+    fn evict_old_frames() {
+        let cfg = clip_cfg();
+
+        // 1 second with a frame every 200ms
+        let mut buffer = ClipBuffer::new();
+        play(&mut buffer, &cfg, 200, &[None; 8]);
+        assert_eq!(buffer.len(), 6);
+
+        // max. 10 frames
+        let mut buffer = ClipBuffer::new();
+        play(&mut buffer, &cfg, 10, &[None; 25]);
+        assert_eq!(buffer.len(), 10);
+    }
+
+    #[test]
+    fn start_over_when_the_source_time_goes_backwards() {
+        let cfg = clip_cfg();
+        let mut buffer = ClipBuffer::new();
+        play(&mut buffer, &cfg, 10, &[None; 5]);
+        assert_eq!(buffer.len(), 5);
+
+        play(&mut buffer, &cfg, 10, &[None; 2]);
+        assert_eq!(buffer.len(), 2);
+    }
+
+    #[test]
+    fn detect_events() {
+        let cfg = clip_cfg();
+        let p = |x, y| Some(cv::Point::new(x, y));
+
+        // lost after 2 frames in the goal area at the top
+        let mut buffer = ClipBuffer::new();
+        let events = play(
+            &mut buffer,
+            &cfg,
+            100,
+            &[p(50, 100), p(50, 110), p(50, 10), None, None, None],
+        );
+        assert_eq!(events, vec![ClipEvent::Goal]);
+
+        // lost in the middle of the field
+        let mut buffer = ClipBuffer::new();
+        let events = play(&mut buffer, &cfg, 10, &[p(50, 100), p(50, 110), None, None]);
+        assert_eq!(events, vec![ClipEvent::PuckLost]);
+
+        // one missing frame isn't lost
+        let mut buffer = ClipBuffer::new();
+        let events = play(&mut buffer, &cfg, 10, &[p(50, 100), None, p(50, 130)]);
+        assert!(events.is_empty());
+
+        // 30 pixels in 10ms
+        let mut buffer = ClipBuffer::new();
+        let events = play(&mut buffer, &cfg, 10, &[p(50, 100), p(50, 130)]);
+        assert_eq!(events, vec![ClipEvent::FastPuck]);
+
+        // 10 pixels in 100ms
+        let mut buffer = ClipBuffer::new();
+        let events = play(&mut buffer, &cfg, 100, &[p(50, 100), p(50, 110)]);
+        assert!(events.is_empty());
+
+        // fast, but below the minimum distance
+        let mut buffer = ClipBuffer::new();
+        let events = play(&mut buffer, &cfg, 2, &[p(50, 100), p(50, 110)]);
+        assert!(events.is_empty());
+
+        // resting puck
+        let mut buffer = ClipBuffer::new();
+        let events = play(&mut buffer, &cfg, 10, &[p(50, 100), p(50, 100), p(50, 100)]);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn disabled_events() {
+        let cfg = Clip {
+            events: vec![ClipEvent::Goal],
+            ..clip_cfg()
+        };
+        let mut buffer = ClipBuffer::new();
+        let events = play(&mut buffer, &cfg, 10, &[Some(cv::Point::new(50, 100)), None, None]);
+        assert!(events.is_empty());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::{
     fs,
@@ -15,10 +15,29 @@ pub type Cfg = Arc<RwLock<Config>>;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub roi: Roi,
+    /// size of the warped table frame - derived from the roi when not set
+    #[serde(default)]
+    pub table_size: Option<TableSize>,
+    #[serde(default)]
+    pub pipeline: Pipeline,
+    /// camera intrinsics to remove the lens distortion - see the `calibrate` tool
+    #[serde(default)]
+    pub calibration: Option<cv::Calibration>,
+    #[serde(default)]
+    pub markers: Markers,
     pub filter: Filter,
     pub pusher: Detector,
     pub puck: Detector,
     pub driver: Driver,
+    #[serde(default)]
+    pub capture: cv::CaptureSettings,
+    #[serde(default)]
+    pub reconnect: cv::RetryPolicy,
+    #[serde(default)]
+    pub clip: Clip,
+    /// background model for `DetectionMethod::MotionAndColor`
+    #[serde(default)]
+    pub background: cv::BackgroundSettings,
 }
 
 impl Config {
@@ -29,7 +48,16 @@ impl Config {
         let path = path.into();
         info!("load configuration from {}", path.display());
         let str_value = fs::read_to_string(&path)?;
-        let config = serde_json::from_str(&str_value)?;
+        let mut config: Config = serde_json::from_str(&str_value)?;
+        let roi = Roi::from_corners(config.roi.to_array())
+            .map_err(|err| format!("invalid roi in {}: {} - select the field again", path.display(), err))?;
+        if roi != config.roi {
+            warn!(
+                "roi corners in {} reordered to left-top, right-top, right-bottom, left-bottom",
+                path.display()
+            );
+            config.roi = roi;
+        }
         Ok(config)
     }
 
@@ -49,6 +77,10 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             roi: Roi::new(960, 550),
+            table_size: None,
+            pipeline: Pipeline::default(),
+            calibration: None,
+            markers: Markers::default(),
             filter: Filter::default(),
             pusher: Detector {
                 color: cv::HSV::unsafe_new(0, 220, 140),
@@ -57,6 +89,8 @@ impl Default for Config {
                 v_offset: 35,
                 area_range: 350.0..3000.0,
                 min_vertices: 8,
+                radius: 20.0,
+                method: DetectionMethod::default(),
             },
             puck: Detector {
                 color: cv::HSV::unsafe_new(60, 120, 120),
@@ -65,13 +99,20 @@ impl Default for Config {
                 v_offset: 50,
                 area_range: 350.0..3000.0,
                 min_vertices: 8,
+                radius: 20.0,
+                method: DetectionMethod::default(),
             },
             driver: Driver::default(),
+            capture: cv::CaptureSettings::default(),
+            reconnect: cv::RetryPolicy::default(),
+            clip: Clip::default(),
+            background: cv::BackgroundSettings::default(),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, Copy, Clone)]
+/// Table corners in the camera image.
+#[derive(Debug, Serialize, Deserialize, Default, Copy, Clone, PartialEq)]
 pub struct Roi {
     pub lt: cv::Point,
     pub rt: cv::Point,
@@ -97,19 +138,37 @@ impl Roi {
         }
     }
 
+    /// roi from the corners in any order - the corners are sorted into
+    /// left-top, right-top, right-bottom, left-bottom.
+    ///
+    /// fails if the corners don't form a convex quadrilateral.
+    pub fn from_corners(corners: [cv::Point; 4]) -> Result<Self> {
+        let roi = Self::from_array(cv::order_corners(&corners));
+        roi.validate()?;
+        Ok(roi)
+    }
+
     pub fn from_vec(v: Vec<cv::Point>) -> Result<Self> {
         if v.len() == 4 {
-            Ok(Self {
-                lt: v[0],
-                rt: v[1],
-                rb: v[2],
-                lb: v[3],
-            })
+            Self::from_corners([v[0], v[1], v[2], v[3]])
         } else {
             Err(format!("Roi::from_vec only valid from vec with 4 elements - given vec: {:?}", v).into())
         }
     }
 
+    /// checks that the corners form a convex quadrilateral in lt/rt/rb/lb order.
+    pub fn validate(&self) -> Result<()> {
+        cv::check_quad(&self.to_array()).map_err(|err| Error::Generic { msg: err.to_string() })?;
+        if cv::order_corners(&self.to_array()) != self.to_array() {
+            return Err(format!(
+                "roi corners not in left-top, right-top, right-bottom, left-bottom order: {:?}",
+                self
+            )
+            .into());
+        }
+        Ok(())
+    }
+
     pub fn from_frame<T>(frame: &cv::Mat<T>) -> Self {
         let w = frame.n_cols();
         let h = frame.n_rows();
@@ -119,6 +178,68 @@ impl Roi {
     pub fn to_array(&self) -> [cv::Point; 4] {
         [self.lt, self.rt, self.rb, self.lb]
     }
+
+    /// largest distance between the corresponding corners of both rois.
+    pub fn max_distance(&self, other: &Roi) -> f64 {
+        self.to_array()
+            .iter()
+            .zip(other.to_array().iter())
+            .map(|(a, b)| a.dist(b))
+            .fold(0., f64::max)
+    }
+}
+
+/// Fiducial markers at the table corners - see `MarkerTracker`.
+///
+/// the markers are placed inside the playing field, flush with its corners - the
+/// outer marker corners are the roi corners (see `cv::MarkerDetector::find_corners`).
+/// needs the `aruco` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Markers {
+    pub enabled: bool,
+    pub dictionary: cv::MarkerDictionary,
+    /// marker ids at the corners: left-top, right-top, right-bottom, left-bottom
+    pub ids: [i32; 4],
+    /// seconds between the checks
+    pub interval: f64,
+    /// corner shift (in pixels) which counts as camera movement
+    pub tolerance: f64,
+}
+
+impl Default for Markers {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dictionary: cv::MarkerDictionary::default(),
+            ids: [0, 1, 2, 3],
+            interval: 5.0,
+            tolerance: 10.0,
+        }
+    }
+}
+
+/// Where the objects are detected.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq)]
+pub enum Pipeline {
+    /// warp the frame to the table plane and detect in the warped frame
+    Warp,
+    /// detect in the raw frame and map only the detections to the table plane
+    Raw,
+    /// like `Raw` - but the area outside of the roi is masked
+    RawMasked,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::Warp
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq)]
+pub struct TableSize {
+    pub width: i32,
+    pub height: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -128,22 +249,97 @@ pub struct Filter {
     pub dilate: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Detector {
     pub color: cv::HSV,
     pub h_offset: i32,
     pub s_offset: i32,
     pub v_offset: i32,
+    /// contour area in pixels of the warped table frame - the raw pipelines (`Pipeline::Raw`,
+    /// `Pipeline::RawMasked`) scale it to the roi size in the raw frame, see `Detector::with_area_scale`
     pub area_range: Range<f64>,
     pub min_vertices: usize,
+    /// object radius in pixels of the warped table frame - the predicted puck path
+    /// bounces off the walls at this distance
+    #[serde(default)]
+    pub radius: f64,
+    #[serde(default)]
+    pub method: DetectionMethod,
 }
 
 impl Detector {
+    /// copy with the area range scaled by the factor - e.g. from the warped table to the raw frame.
+    ///
+    /// the circle sizes of `DetectionMethod::HoughCircles` are scaled linear (square root of the factor).
+    pub fn with_area_scale(&self, scale: f64) -> Detector {
+        let method = match &self.method {
+            DetectionMethod::HoughCircles(params) => DetectionMethod::HoughCircles(params.scaled(scale.sqrt())),
+            method => method.clone(),
+        };
+        Detector {
+            area_range: self.area_range.start * scale..self.area_range.end * scale,
+            method,
+            ..self.clone()
+        }
+    }
+
     pub fn color_range(&self) -> Result<cv::HSVRange> {
         let color_offsets = (self.h_offset, self.s_offset, self.v_offset);
         let color_range = cv::HSVRange::from_hsv(&self.color, color_offsets)?;
         Ok(color_range)
     }
+
+    /// sets the color and the offsets from the color statistics of a sample.
+    pub fn fit(&mut self, stats: &cv::ColorStats) {
+        let (color, (h_offset, s_offset, v_offset)) = stats.fit();
+        self.color = color;
+        self.h_offset = h_offset;
+        self.s_offset = s_offset;
+        self.v_offset = v_offset;
+    }
+}
+
+/// How a `Detector` finds its object.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum DetectionMethod {
+    /// contours of the pixels in the color range - filtered by the area and the number of vertices
+    Contours,
+    /// circles of the given size - independent of the color, see `cv::HoughCircles`
+    HoughCircles(cv::HoughCircles),
+    /// like `Contours` - but only the moving pixels, see `Config::background`
+    MotionAndColor,
+    /// best match of a template image - independent of the color range
+    Template(Template),
+}
+
+impl Default for DetectionMethod {
+    fn default() -> Self {
+        DetectionMethod::Contours
+    }
+}
+
+/// Template matching settings - see `cv::TemplateMatcher`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Template {
+    /// template image - captured in the gui
+    pub path: PathBuf,
+    /// matches with a lower score are ignored: -1.0 ..= 1.0
+    pub min_score: f64,
+    /// template scales - e.g. `[0.9, 1.0, 1.1]` when the object size varies
+    pub scales: Vec<f64>,
+}
+
+impl Template {
+    pub fn new<P>(path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            min_score: 0.7,
+            scales: vec![1.0],
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -162,3 +358,66 @@ impl Default for Driver {
         }
     }
 }
+
+/// Settings for the pre-event clips - see `ClipBuffer`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Clip {
+    /// seconds before the event which are kept in the ring buffer
+    pub seconds: f64,
+    /// upper bound for the frames in the ring buffer - limits the memory usage.
+    ///
+    /// the frames are kept uncompressed: ~0.9 MB per 640x480 frame
+    pub max_frames: usize,
+    /// each clip is saved in a new subdirectory
+    pub dir: PathBuf,
+    pub codec: cv::FourCC,
+    pub fps: f64,
+    /// events which save a clip - the hotkey always works
+    pub events: Vec<ClipEvent>,
+    /// number of frames without a puck before the puck counts as lost
+    pub lost_frames: usize,
+    /// distance (in pixels) to the top / bottom edge of the field, where a lost puck counts as goal
+    pub goal_margin: i32,
+    /// minimum puck speed (in pixels per millisecond of source time) for `ClipEvent::FastPuck`
+    pub fast_puck_speed: f64,
+    /// minimum distance (in pixels) the puck must move for `ClipEvent::FastPuck` - ignores detection jitter
+    pub fast_puck_min_dist: f64,
+}
+
+impl Default for Clip {
+    fn default() -> Self {
+        Self {
+            seconds: 3.0,
+            max_frames: 90,
+            dir: PathBuf::from("clips"),
+            codec: cv::FourCC::default(),
+            fps: 30.0,
+            events: vec![ClipEvent::Goal, ClipEvent::PuckLost],
+            lost_frames: 5,
+            goal_margin: 30,
+            fast_puck_speed: 2.0,
+            fast_puck_min_dist: 20.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_config_with_invalid_roi() {
+        let path = std::env::temp_dir().join(format!("airhobot-config-{}.json", std::process::id()));
+        let p = cv::Point::new(10, 10);
+        let cfg = Config {
+            roi: Roi::from_array([p, p, cv::Point::new(100, 100), cv::Point::new(10, 100)]),
+            ..Config::default()
+        };
+        cfg.save(&path).unwrap();
+
+        let err = Config::load(&path).unwrap_err();
+        assert!(err.to_string().contains("invalid roi"));
+        fs::remove_file(&path).unwrap();
+    }
+}
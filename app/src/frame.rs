@@ -0,0 +1,77 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Identifies the input source of a frame - e.g. `cam:0` or `video:game.avi`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceId(Arc<str>);
+
+impl SourceId {
+    pub fn new<S: AsRef<str>>(id: S) -> Self {
+        SourceId(Arc::from(id.as_ref()))
+    }
+}
+
+impl fmt::Display for SourceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Frame from a input source with its metadata.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub mat: cv::Mat<cv::BGR>,
+    /// source timestamp, relative to the start of the source.
+    ///
+    ///   - video files: the position in the video
+    ///   - image sequences: the timestamp from the sidecar file
+    ///   - cams / network streams: the capture clock
+    pub ts: Duration,
+    /// sequence number - starts with 0 for each source
+    pub seq: u64,
+    pub source_id: SourceId,
+}
+
+impl Frame {
+    /// elapsed source time since the `earlier` frame.
+    ///
+    /// `None` if the frames are from different sources or the source
+    /// time went backwards (looping video).
+    pub fn since(&self, earlier: &Frame) -> Option<Duration> {
+        if self.source_id != earlier.source_id {
+            return None;
+        }
+        self.ts.checked_sub(earlier.ts)
+    }
+}
+
+/// black 100x200 (width x height) frame from `video:game.avi` at the given source time - for the tests.
+#[cfg(test)]
+pub fn test_frame(ts_millis: u64, seq: u64) -> Frame {
+    Frame {
+        mat: cv::Mat::<cv::BGR>::new(200, 100, cv::RGB::black()).unwrap(),
+        ts: Duration::from_millis(ts_millis),
+        seq,
+        source_id: SourceId::new("video:game.avi"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn since() {
+        let earlier = test_frame(100, 3);
+        let later = test_frame(250, 8);
+        assert_eq!(later.since(&earlier), Some(Duration::from_millis(150)));
+
+        // looping video
+        assert_eq!(earlier.since(&later), None);
+        // other source
+        let cam = Frame {
+            source_id: SourceId::new("cam:0"),
+            ..test_frame(250, 8)
+        };
+        assert_eq!(cam.since(&earlier), None);
+    }
+}
@@ -7,10 +7,14 @@
 use log::{debug, trace};
 use std::time::Duration;
 
+mod clip;
 mod config;
 mod error;
+mod frame;
+mod markers;
 mod predict;
 //mod puck;
+mod recorder;
 mod source;
 mod state;
 mod utils;
@@ -18,11 +22,15 @@ mod utils;
 pub mod prelude {
     pub type Result<T> = std::result::Result<T, Error>;
 
+    pub use crate::clip::*;
     pub use crate::config::*;
-    pub use crate::detect;
     pub use crate::error::*;
+    pub use crate::frame::*;
+    pub use crate::markers::*;
     pub use crate::predict::predict;
+    pub use crate::{detect, detect_circles, detect_moving, detect_template};
     //  pub use crate::puck::*;
+    pub use crate::recorder::*;
     pub use crate::source::*;
     pub use crate::state::State;
     pub use crate::utils::*;
@@ -36,11 +44,28 @@ pub fn detect<S>(what: S, detector: &Detector, frame: &cv::Mat<cv::HSV>) -> Resu
 where
     S: Into<String>,
 {
-    let what = what.into();
-
     // mask the colors which are not in the given color range
     let mut masked = frame.in_range(&detector.color_range()?);
 
+    filter_contours(&what.into(), detector, &mut masked)
+}
+
+/// like `detect` - but only the pixels in the foreground mask (see `cv::BackgroundSubtractor`).
+pub fn detect_moving<S>(
+    what: S,
+    detector: &Detector,
+    frame: &cv::Mat<cv::HSV>,
+    foreground: &cv::Mat<cv::Gray>,
+) -> Result<cv::Contours>
+where
+    S: Into<String>,
+{
+    let mut masked = frame.in_range(&detector.color_range()?).bitwise_and(foreground)?;
+
+    filter_contours(&what.into(), detector, &mut masked)
+}
+
+fn filter_contours(what: &str, detector: &Detector, masked: &mut cv::Mat<cv::Gray>) -> Result<cv::Contours> {
     // find all countours in the masked frame
     let contours_all = masked.find_contours();
 
@@ -82,6 +107,30 @@ where
     Ok(contours)
 }
 
+pub fn detect_circles<S>(what: S, params: &cv::HoughCircles, frame: &cv::Mat<cv::Gray>) -> Result<Vec<cv::Circle>>
+where
+    S: Into<String>,
+{
+    let circles = params.detect(frame)?;
+    debug!("{} detector - {} circles detected", what.into(), circles.len());
+    Ok(circles)
+}
+
+/// best template match with at least the minimum score of the template settings.
+pub fn detect_template<S>(
+    what: S,
+    template: &Template,
+    matcher: &cv::TemplateMatcher,
+    frame: &cv::Mat<cv::BGR>,
+) -> Result<Option<cv::TemplateMatch>>
+where
+    S: Into<String>,
+{
+    let found = matcher.find(frame)?;
+    debug!("{} detector - template match: {:?}", what.into(), found);
+    Ok(found.filter(|m| m.score >= template.min_score))
+}
+
 #[derive(Debug, PartialEq)]
 pub enum PuckSpeed {
     Slow,
@@ -89,8 +138,26 @@ pub enum PuckSpeed {
     Wormhole,
 }
 impl PuckSpeed {
+    /// speed of the puck between the two frames - uses the source time of the frames,
+    /// so replays of recorded videos give the same speeds as live play.
+    ///
+    /// `None` if no source time elapsed between the frames.
+    pub fn between(from: (&Frame, &cv::Point), to: (&Frame, &cv::Point)) -> Option<Self> {
+        let dur = to.0.since(from.0)?;
+        if dur == Duration::from_secs(0) {
+            return None;
+        }
+        let dist = to.1.dist(from.1);
+        Some(Self::new(dur, dist))
+    }
+
+    /// a puck which didn't move (`dist` is zero) is `PuckSpeed::Slow`.
     pub fn new(dur: Duration, dist: f64) -> Self {
-        let speed = dbg!(dur.as_millis() as f64 / dist);
+        if dist <= 0.0 {
+            return PuckSpeed::Slow;
+        }
+        let speed = dur.as_millis() as f64 / dist;
+        trace!("puck speed: {} ms per pixel", speed);
         if speed < 1.5 {
             PuckSpeed::Slow
         } else if speed < 2.0 {
@@ -123,3 +190,27 @@ impl PuckSpeed {
 //         Some(Point::new(300, 237))
 //     );
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn puck_speed_from_the_source_time() {
+        let (p1, p2) = (cv::Point::new(0, 0), cv::Point::new(100, 0));
+
+        // 1ms per pixel - independent of the processing time
+        let (from, to) = (test_frame(1000, 30), test_frame(1100, 33));
+        assert_eq!(PuckSpeed::between((&from, &p1), (&to, &p2)), Some(PuckSpeed::Slow));
+
+        assert_eq!(
+            PuckSpeed::between((&from, &p1), (&test_frame(1300, 39), &p2)),
+            Some(PuckSpeed::Wormhole)
+        );
+        assert_eq!(PuckSpeed::between((&to, &p1), (&from, &p2)), None);
+
+        // resting puck / no elapsed source time
+        assert_eq!(PuckSpeed::between((&from, &p1), (&to, &p1)), Some(PuckSpeed::Slow));
+        assert_eq!(PuckSpeed::between((&from, &p1), (&test_frame(1000, 31), &p2)), None);
+    }
+}
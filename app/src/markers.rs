@@ -0,0 +1,90 @@
+use crate::prelude::*;
+use log::{info, warn};
+use std::time::Duration;
+
+/// Keeps the roi in sync with the fiducial markers at the table corners.
+///
+/// The markers are checked each `Markers::interval` seconds (source time).
+/// When the corners moved more than `Markers::tolerance`, the camera
+/// was moved - the new roi is returned and a warning is logged.
+#[derive(Default)]
+pub struct MarkerTracker {
+    detector: Option<(cv::MarkerDictionary, cv::MarkerDetector)>,
+    last_check: Option<Duration>,
+    /// `true` after the first roi from the markers
+    located: bool,
+    /// `true` if the detector is not available - e.g. without the `aruco` feature
+    unavailable: bool,
+}
+
+impl MarkerTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// checks the markers in the original frame of the state.
+    ///
+    /// returns the roi from the markers, if it differs from the configured roi.
+    pub fn check(&mut self, state: &State) -> Result<Option<Roi>> {
+        let cfg = state.cfg.read()?;
+        let markers = &cfg.markers;
+        if !markers.enabled || self.unavailable {
+            return Ok(None);
+        }
+
+        let ts = state.frame_orig.ts;
+        let interval = Duration::from_secs_f64(markers.interval.max(0.0));
+        if let Some(last) = self.last_check {
+            // the source time goes backwards when seeking
+            if ts >= last && ts - last < interval {
+                return Ok(None);
+            }
+        }
+        self.last_check = Some(ts);
+
+        if self.detector.as_ref().map(|(dictionary, _)| *dictionary) != Some(markers.dictionary) {
+            match cv::MarkerDetector::new(markers.dictionary) {
+                Ok(detector) => self.detector = Some((markers.dictionary, detector)),
+                Err(err) => {
+                    warn!("table markers disabled: {}", err);
+                    self.unavailable = true;
+                    return Ok(None);
+                }
+            }
+        }
+        let detector = match &self.detector {
+            Some((_, detector)) => detector,
+            None => return Ok(None),
+        };
+
+        let corners = match detector.find_corners(&state.frame_orig.mat, &markers.ids)? {
+            Some(corners) => corners,
+            None => {
+                warn!("table markers {:?} not found", markers.ids);
+                return Ok(None);
+            }
+        };
+
+        let roi = match Roi::from_corners(corners) {
+            Ok(roi) => roi,
+            Err(err) => {
+                warn!("invalid roi from the table markers: {}", err);
+                return Ok(None);
+            }
+        };
+        let shift = roi.max_distance(&cfg.roi);
+        if !self.located {
+            self.located = true;
+            info!("roi from the table markers: {:?}", roi);
+            Ok(Some(roi))
+        } else if shift > markers.tolerance {
+            warn!(
+                "camera moved - the table corners shifted by {:.1}px - update the roi",
+                shift
+            );
+            Ok(Some(roi))
+        } else {
+            Ok(None)
+        }
+    }
+}
@@ -1,28 +1,46 @@
-use xstd::prelude::*;
+/// Maximum number of wall bounces in a predicted path.
+const MAX_BOUNCES: usize = 8;
 
-pub fn predict<T>(frame: &cv::Mat<T>, from: &cv::Point, to: &cv::Point) -> Vec<cv::Point> {
+/// Predicted path of the puck center moving from `from` to `to` - until it reaches the bottom of the frame.
+///
+/// The puck bounces off the frame edges, when its edge (`radius` away from the center)
+/// touches them. The path starts with `to`, followed by the bounce points and the point at the bottom.
+///
+/// `None` if the puck doesn't reach the bottom - it moves horizontally (or not at all),
+/// or it bounces more than `MAX_BOUNCES` times.
+pub fn predict<T>(frame: &cv::Mat<T>, from: &cv::Point, to: &cv::Point, radius: f64) -> Option<Vec<cv::Point>> {
+    let (width, height) = (frame.n_cols(), frame.n_rows());
+    let table = cv::Polygon::from_points(&[
+        cv::Point::new(0, 0),
+        cv::Point::new(width, 0),
+        cv::Point::new(width, height),
+        cv::Point::new(0, height),
+    ]);
     // FIXME
-    let y = frame.n_rows() - 10;
+    let goal = cv::Segment::from_points(&cv::Point::new(0, height - 10), &cv::Point::new(width, height - 10));
 
-    if from.x() == to.x() {
-        return vec![*to, cv::Point::new(to.x(), y)];
+    let mut ray = cv::Ray::through(&cv::Vec2::from(from), &cv::Vec2::from(to));
+    let mut path = vec![*to];
+    // no goal crossing without a vertical movement
+    if ray.dir().y().abs() < f64::EPSILON {
+        return None;
     }
 
-    let m = (to.y() - from.y()) as f32 / (to.x() - from.x()) as f32;
-    let x = ((y - to.y()) as f32 / m + to.x() as f32) as i32;
-    let p = cv::Point::new(x, y);
-
-    if x >= 0 && x <= frame.n_cols() {
-        vec![*to, p]
-    } else {
-        let bounce_x = ifte!(x <= 0, 0, frame.n_cols());
-        let bounce_y = ((bounce_x - to.x()) as f32 * m + to.y() as f32) as i32;
-        let b = cv::Point::new(bounce_x, bounce_y);
-        let p_ = cv::Point::new(((y - bounce_y) as f32 / (-m) + bounce_x as f32) as i32, y);
-        let mut v = vec![*to, b, p_];
-        v.append(&mut predict(frame, &b, &p_));
-        v
+    for _ in 0..=MAX_BOUNCES {
+        let goal_hit = ray.intersection(&goal);
+        match table.sweep_circle(&ray, radius.max(0.)) {
+            Some(hit) if goal_hit.map_or(true, |(t, _)| hit.t < t) => {
+                path.push(hit.center.round());
+                ray = cv::Ray::new(hit.center, hit.reflect(&ray.dir()));
+            }
+            _ => {
+                let (_, p) = goal_hit?;
+                path.push(p.round());
+                return Some(path);
+            }
+        }
     }
+    None
 }
 
 // pub fn predict(state: &State, cfg: &Config, from: &Pos, to: &Pos, y: i32) -> (Pos, Option<Pos>) {
@@ -68,3 +86,41 @@ pub fn predict<T>(frame: &cv::Mat<T>, from: &cv::Point, to: &cv::Point) -> Vec<c
 //         None
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn predict_with_bounce() {
+        let frame = cv::Mat::<cv::BGR>::new(110, 100, cv::RGB::black()).unwrap();
+        let p = cv::Point::new;
+
+        // vertical lines have no slope
+        assert_eq!(
+            predict(&frame, &p(50, 0), &p(50, 10), 0.),
+            Some(vec![p(50, 10), p(50, 100)])
+        );
+        assert_eq!(
+            predict(&frame, &p(40, 0), &p(50, 10), 0.),
+            Some(vec![p(50, 10), p(100, 60), p(60, 100)])
+        );
+        // the puck edge touches the wall
+        assert_eq!(
+            predict(&frame, &p(40, 0), &p(50, 10), 5.),
+            Some(vec![p(50, 10), p(95, 55), p(50, 100)])
+        );
+    }
+
+    #[test]
+    fn predict_without_goal() {
+        let frame = cv::Mat::<cv::BGR>::new(110, 100, cv::RGB::black()).unwrap();
+        let p = cv::Point::new;
+
+        // horizontal / no movement
+        assert_eq!(predict(&frame, &p(10, 50), &p(20, 50), 0.), None);
+        assert_eq!(predict(&frame, &p(20, 50), &p(20, 50), 0.), None);
+        // too many bounces before the goal line
+        assert_eq!(predict(&frame, &p(10, 10), &p(90, 11), 0.), None);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::prelude::*;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// File name of the raw camera stream in a recording directory.
+pub const RAW_VIDEO: &str = "raw.avi";
+/// File name of the annotated frame stream in a recording directory.
+pub const ANNOTATED_VIDEO: &str = "annotated.avi";
+/// File name of the per-frame sidecar file in a recording directory.
+pub const FRAMES_SIDECAR: &str = "frames.jsonl";
+
+/// Metadata of a recorded frame - one json object per line in the sidecar file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameRecord {
+    /// index of the frame in the recorded videos
+    pub index: u64,
+    /// sequence number of the frame from the input source
+    pub seq: u64,
+    /// source timestamp in milliseconds
+    pub ts: u64,
+    pub source_id: String,
+    pub puck: Option<cv::Point>,
+    pub pusher: Option<cv::Point>,
+}
+
+impl FrameRecord {
+    /// record of the given state - the index is assigned by the `Recorder`.
+    pub fn from_state(state: &State) -> Self {
+        Self {
+            index: 0,
+            seq: state.frame_orig.seq,
+            ts: state.frame_orig.ts.as_millis() as u64,
+            source_id: state.frame_orig.source_id.to_string(),
+            puck: state.puck,
+            pusher: state.pusher,
+        }
+    }
+}
+
+/// Records a session into a directory:
+///
+///   - `raw.avi`: the frames from the input source
+///   - `annotated.avi`: the processed frames with the detections (optional)
+///   - `frames.jsonl`: the timestamps and detections per frame - see `FrameRecord`
+///
+/// The videos are created with the first frame, because the frame size is unknown before.
+/// Annotated frames with another size (e.g. after a roi change) are scaled to this size,
+/// so both videos stay in line with the frame indices in the sidecar file.
+///
+/// Frames which were already recorded (paused or seeking back) are skipped.
+pub struct Recorder {
+    dir: PathBuf,
+    codec: cv::FourCC,
+    fps: f64,
+    raw: Option<cv::VideoWriter>,
+    annotated: Option<cv::VideoWriter>,
+    sidecar: BufWriter<File>,
+    index: u64,
+    last_seq: Option<u64>,
+    last_position: Option<u64>,
+}
+
+impl Recorder {
+    pub fn create<P>(dir: P, codec: cv::FourCC, fps: f64) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        info!("record session to {} with codec {}", dir.display(), codec);
+        let sidecar = BufWriter::new(File::create(dir.join(FRAMES_SIDECAR))?);
+        Ok(Self {
+            dir,
+            codec,
+            fps,
+            raw: None,
+            annotated: None,
+            sidecar,
+            index: 0,
+            last_seq: None,
+            last_position: None,
+        })
+    }
+
+    /// writes the original and the annotated frame and the detections of the given state.
+    ///
+    /// `position` is the frame index in the source (see `Source::frame_index`) - a frame
+    /// at or before the last recorded position was already recorded and is skipped.
+    pub fn record(&mut self, state: &State, position: Option<u64>) -> Result<()> {
+        let recorded = |last: Option<u64>, current: Option<u64>| match (last, current) {
+            (Some(last), Some(current)) => current <= last,
+            _ => false,
+        };
+        if recorded(self.last_position, position) {
+            debug!("skip recorded frame at position {:?}", position);
+            return Ok(());
+        }
+        if position.is_some() {
+            self.last_position = position;
+        }
+
+        self.write(
+            &state.frame_orig.mat,
+            Some(&state.frame),
+            FrameRecord::from_state(state),
+        )
+    }
+
+    /// writes the raw frame, the optional annotated frame and the frame record.
+    ///
+    /// skips the frame if a frame with the same or a later sequence number was written.
+    pub fn write(
+        &mut self,
+        raw: &cv::Mat<cv::BGR>,
+        annotated: Option<&cv::Mat<cv::BGR>>,
+        mut record: FrameRecord,
+    ) -> Result<()> {
+        if self.last_seq.map_or(false, |last| record.seq <= last) {
+            debug!("skip recorded frame {}", record.seq);
+            return Ok(());
+        }
+
+        let (dir, codec, fps) = (&self.dir, self.codec, self.fps);
+        let open = |name: &str, frame: &cv::Mat<cv::BGR>| {
+            cv::VideoWriter::with_codec(&dir.join(name), codec, fps, frame.n_cols(), frame.n_rows())
+        };
+
+        if self.raw.is_none() {
+            self.raw = Some(open(RAW_VIDEO, raw)?);
+        }
+        if let Some(writer) = &mut self.raw {
+            writer.write(raw)?;
+        }
+
+        if let Some(annotated) = annotated {
+            if self.annotated.is_none() {
+                self.annotated = Some(open(ANNOTATED_VIDEO, annotated)?);
+            }
+            if let Some(writer) = &mut self.annotated {
+                // the size of the annotated frames changes with the roi
+                if annotated.n_cols() != writer.width() || annotated.n_rows() != writer.height() {
+                    writer.write(&annotated.resize(writer.width(), writer.height())?)?;
+                } else {
+                    writer.write(annotated)?;
+                }
+            }
+        }
+
+        self.last_seq = Some(record.seq);
+        record.index = self.index;
+        serde_json::to_writer(&mut self.sidecar, &record)?;
+        self.sidecar.write_all(b"\n")?;
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let _ = self.sidecar.flush();
+    }
+}
+
+/// reads the sidecar file of a recording.
+pub fn read_sidecar(path: &Path) -> Result<Vec<FrameRecord>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            records.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(records)
+}
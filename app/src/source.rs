@@ -1,10 +1,27 @@
 use crate::prelude::*;
-use std::path::PathBuf;
+use log::{debug, warn};
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-/// Input source. Can be an Image, Video or Cam.
+/// Frame interval for image sequences without a timestamps sidecar file (30 fps).
+const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_micros(33_333);
+
+/// Range of the playback speed multiplier.
+const MIN_SPEED: f64 = 0.125;
+const MAX_SPEED: f64 = 8.0;
+
+/// Input source. Can be an Image, Image-Sequence, Video, Cam or network stream.
 ///
 /// `Source` implements iterator, so you can easy loop
 /// over the source and receive a new frame in each iteration.
+/// Each frame carries the source timestamp, a sequence number
+/// and the source id - see `Frame`.
+///
+/// Cams and network streams are read in a background thread, which
+/// keeps only the newest frame - see `cv::ThreadedCapture`. When a frame
+/// can't be read, they reconnect according to the `cv::RetryPolicy`.
 ///
 /// ```
 /// use airhobot::prelude::*;
@@ -13,16 +30,62 @@ use std::path::PathBuf;
 ///   // process frame
 /// }
 /// ```
-pub enum Source {
+pub struct Source {
+    id: SourceId,
+    kind: Kind,
+    seq: u64,
+    started: Instant,
+    looping: bool,
+    speed: f64,
+}
+
+enum Kind {
     Image(cv::Mat<cv::BGR>),
-    Stream(Box<dyn Iterator<Item = cv::Mat<cv::BGR>>>),
+    Images(cv::ImageSequence),
+    /// `timestamps`: source timestamps per frame index from a recording
+    Video {
+        capture: cv::VideoCapture,
+        timestamps: Option<Vec<Duration>>,
+    },
+    /// the capture thread is started with the first `grab`
+    Live {
+        capture: Option<cv::VideoCapture>,
+        thread: Option<cv::ThreadedCapture>,
+    },
 }
 
 impl Source {
     /// use the cam as input source.
     pub fn cam(device_id: i32) -> Result<Self> {
-        let iter = cv::VideoCapture::open_device(device_id)?.into_iter();
-        Ok(Self::Stream(Box::new(iter)))
+        Self::cam_with_settings(device_id, &cv::CaptureSettings::default())
+    }
+
+    /// use the cam as input source and apply the given capture settings.
+    pub fn cam_with_settings(device_id: i32, settings: &cv::CaptureSettings) -> Result<Self> {
+        let mut capture = cv::VideoCapture::open_device(device_id)?;
+        capture.apply(settings)?;
+        Ok(Self::live(format!("cam:{}", device_id), capture))
+    }
+
+    /// use the GStreamer pipeline as input source.
+    pub fn gstreamer(pipeline: &str) -> Result<Self> {
+        let capture = cv::VideoCapture::open_gstreamer(pipeline)?;
+        Ok(Self::live(format!("gstreamer:{}", pipeline), capture))
+    }
+
+    /// use the network stream (MJPEG over HTTP, RTSP) as input source.
+    ///
+    /// supported schemes: `http://`, `https://` and `rtsp://` - use
+    /// `Source::gstreamer` for other protocols or a custom decoder.
+    pub fn url(url: &str) -> Result<Self> {
+        let supported = ["http://", "https://", "rtsp://"];
+        if !supported.iter().any(|scheme| url.starts_with(scheme)) {
+            return Err(Error::Arguments {
+                msg: format!("unsupported url: '{}' - expected: http://, https:// or rtsp://", url),
+            });
+        }
+        let capture = cv::VideoCapture::open_url(url)?;
+        Ok(Self::live(format!("url:{}", url), capture))
     }
 
     /// use the video file as input source.
@@ -31,8 +94,36 @@ impl Source {
         P: Into<PathBuf>,
     {
         let p = p.into();
-        let iter = cv::VideoCapture::open_file(&p)?.into_iter();
-        Ok(Self::Stream(Box::new(iter)))
+        let capture = cv::VideoCapture::open_file(&p)?;
+        let kind = Kind::Video {
+            capture,
+            timestamps: None,
+        };
+        Ok(Self::new(format!("video:{}", p.display()), kind))
+    }
+
+    /// use the raw video of a recording (see `Recorder`) as input source.
+    ///
+    /// the frames are stamped with the timestamps from the recording,
+    /// so the replay gives the same results as the live session.
+    pub fn recording<P>(dir: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        let capture = cv::VideoCapture::open_file(&dir.join(RAW_VIDEO))?;
+        let records = read_sidecar(&dir.join(FRAMES_SIDECAR))?;
+        let mut timestamps = vec![Duration::default(); records.len()];
+        for r in records {
+            if let Some(ts) = timestamps.get_mut(r.index as usize) {
+                *ts = Duration::from_millis(r.ts);
+            }
+        }
+        let kind = Kind::Video {
+            capture,
+            timestamps: Some(timestamps),
+        };
+        Ok(Self::new(format!("recording:{}", dir.display()), kind))
     }
 
     /// use the image file as input source.
@@ -44,21 +135,280 @@ impl Source {
         P: Into<PathBuf>,
     {
         let p = p.into();
-        let mat = cv::imread(p)?;
-        Ok(Self::Image(mat))
+        let mat = cv::imread(&p)?;
+        Ok(Self::new(format!("image:{}", p.display()), Kind::Image(mat)))
     }
 
-    pub fn grab(&mut self) -> Option<cv::Mat<cv::BGR>> {
-        match self {
-            Source::Image(frame) => Some(frame.clone()),
-            Source::Stream(iter) => iter.next(),
+    /// use the numbered images (PNG / JPEG) in the given directory as input source.
+    ///
+    /// the images are returned in natural sort order. if `looping` is set,
+    /// the sequence starts from the beginning after the last image.
+    /// see `cv::ImageSequence` for the optional timestamps sidecar file - without
+    /// the sidecar file, the frames are timestamped with a 30 fps frame interval.
+    pub fn image_sequence<P>(dir: P, looping: bool) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let dir = dir.into();
+        let images = cv::ImageSequence::open(&dir)?.looping(looping);
+        Ok(Self::new(format!("images:{}", dir.display()), Kind::Images(images)))
+    }
+
+    fn live(id: String, capture: cv::VideoCapture) -> Self {
+        let capture = capture.with_retry_policy(cv::RetryPolicy::default());
+        Self::new(
+            id,
+            Kind::Live {
+                capture: Some(capture),
+                thread: None,
+            },
+        )
+    }
+
+    /// sets the retry policy for live sources (cams, network streams).
+    ///
+    /// has no effect on other sources or after the first frame was grabbed.
+    pub fn with_retry_policy(mut self, policy: cv::RetryPolicy) -> Self {
+        if let Kind::Live {
+            capture: Some(capture), ..
+        } = &mut self.kind
+        {
+            capture.set_retry_policy(Some(policy));
         }
+        self
+    }
+
+    fn new(id: String, kind: Kind) -> Self {
+        Self {
+            id: SourceId::new(id),
+            kind,
+            seq: 0,
+            started: Instant::now(),
+            looping: false,
+            speed: 1.0,
+        }
+    }
+
+    pub fn id(&self) -> &SourceId {
+        &self.id
+    }
+
+    /// start from the beginning when the end of a video or image sequence is reached.
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.set_looping(looping);
+        self
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+        if let Kind::Images(images) = &mut self.kind {
+            images.set_looping(looping);
+        }
+    }
+
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// playback speed multiplier - the consumer paces the frames with it.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(MIN_SPEED).min(MAX_SPEED);
+    }
+
+    /// `true` if the source supports the playback controls (seek, step back).
+    pub fn is_seekable(&self) -> bool {
+        matches!(self.kind, Kind::Video { .. } | Kind::Images(_))
+    }
+
+    /// index of the last grabbed frame in a video or image sequence.
+    pub fn frame_index(&self) -> Option<u64> {
+        match &self.kind {
+            Kind::Video { capture, .. } => capture.frame_position().ok()?.checked_sub(1),
+            Kind::Images(images) => (images.position() as u64).checked_sub(1),
+            _ => None,
+        }
+    }
+
+    /// number of frames in a video or image sequence.
+    pub fn frame_count(&self) -> Option<u64> {
+        match &self.kind {
+            Kind::Video { capture, .. } => capture.frame_count().ok(),
+            Kind::Images(images) => Some(images.len() as u64),
+            _ => None,
+        }
+    }
+
+    /// seeks to the frame with the given index - the next `grab` returns this frame.
+    pub fn seek_frame(&mut self, n: u64) -> Result<()> {
+        match &mut self.kind {
+            Kind::Video { capture, .. } => capture.set_frame_position(n)?,
+            Kind::Images(images) => images.seek(n as usize),
+            _ => return Err(not_seekable(&self.id)),
+        }
+        Ok(())
+    }
+
+    /// seeks to the given source timestamp - the next `grab` returns the frame at this position.
+    pub fn seek(&mut self, ts: Duration) -> Result<()> {
+        match &mut self.kind {
+            Kind::Video {
+                capture,
+                timestamps: Some(timestamps),
+            } => {
+                let n = timestamps.iter().position(|t| *t >= ts).unwrap_or(timestamps.len());
+                capture.set_frame_position(n as u64)?
+            }
+            Kind::Video { capture, .. } => capture.set_position(ts)?,
+            Kind::Images(images) => {
+                if !images.seek_timestamp(ts) {
+                    let n = ts.as_micros() / DEFAULT_FRAME_INTERVAL.as_micros();
+                    images.seek(n as usize)
+                }
+            }
+            _ => return Err(not_seekable(&self.id)),
+        }
+        Ok(())
+    }
+
+    /// seeks relative to the last grabbed frame - negative values seek backwards.
+    pub fn seek_relative(&mut self, frames: i64) -> Result<()> {
+        let current = self.frame_index().ok_or_else(|| not_seekable(&self.id))? as i64;
+        self.seek_frame((current + frames).max(0) as u64)
+    }
+
+    /// the next `grab` returns the frame before the last grabbed frame.
+    pub fn step_back(&mut self) -> Result<()> {
+        self.seek_relative(-1)
+    }
+
+    /// number of frames which were captured but never consumed.
+    ///
+    /// only live sources (cams, network streams) drop frames.
+    pub fn dropped_frames(&self) -> u64 {
+        match &self.kind {
+            Kind::Live {
+                thread: Some(thread), ..
+            } => thread.dropped(),
+            _ => 0,
+        }
+    }
+
+    pub fn grab(&mut self) -> Option<Frame> {
+        let started = self.started;
+        let looping = self.looping;
+        let (mat, ts) = match &mut self.kind {
+            Kind::Image(mat) => (mat.clone(), started.elapsed()),
+            Kind::Images(images) => {
+                let mat = images.next()?;
+                let ts = images
+                    .timestamp()
+                    .unwrap_or_else(|| DEFAULT_FRAME_INTERVAL * images.position().saturating_sub(1) as u32);
+                (mat, ts)
+            }
+            Kind::Video { capture, timestamps } => {
+                let mat = match capture.next() {
+                    Some(mat) => mat,
+                    None if looping => {
+                        debug!("end of video reached - start from the beginning");
+                        capture.set_frame_position(0).ok()?;
+                        capture.next()?
+                    }
+                    None => return None,
+                };
+                let recorded = timestamps.as_ref().and_then(|timestamps| {
+                    let n = capture.frame_position().ok()?.checked_sub(1)?;
+                    timestamps.get(n as usize).copied()
+                });
+                let ts = recorded.unwrap_or_else(|| {
+                    capture.position().unwrap_or_else(|err| {
+                        warn!("unable to read the video position: {}", err);
+                        started.elapsed()
+                    })
+                });
+                (mat, ts)
+            }
+            Kind::Live { capture, thread } => {
+                if let Some(capture) = capture.take() {
+                    // the signal ends the reconnects, when the thread is dropped
+                    let stop = capture.stop_signal();
+                    *thread = Some(cv::ThreadedCapture::spawn_stoppable(capture, stop));
+                }
+                let frame = thread.as_mut()?.grab()?;
+                (frame.mat, frame.captured_at.saturating_duration_since(started))
+            }
+        };
+
+        let seq = self.seq;
+        self.seq += 1;
+        Some(Frame {
+            mat,
+            ts,
+            seq,
+            source_id: self.id.clone(),
+        })
     }
 }
 
 impl Iterator for Source {
-    type Item = cv::Mat<cv::BGR>;
+    type Item = Frame;
     fn next(&mut self) -> Option<Self::Item> {
         self.grab()
     }
 }
+
+fn not_seekable(id: &SourceId) -> Error {
+    Error::Arguments {
+        msg: format!(
+            "source '{}' doesn't support seeking - only videos and image sequences",
+            id
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cv::testing;
+
+    fn assert_index(frame: &Frame, i: u8) {
+        assert_eq!(testing::image_index(&frame.mat), i);
+    }
+
+    #[test]
+    fn seek_and_step_back() {
+        let dir = testing::image_dir("source-seek", 4);
+        let mut source = Source::image_sequence(dir.path(), false).unwrap();
+        assert!(source.is_seekable());
+        assert_eq!(source.frame_count(), Some(4));
+
+        assert_index(&source.grab().unwrap(), 0);
+        assert_index(&source.grab().unwrap(), 1);
+        assert_index(&source.grab().unwrap(), 2);
+        assert_eq!(source.frame_index(), Some(2));
+
+        source.step_back().unwrap();
+        assert_index(&source.grab().unwrap(), 1);
+
+        // without a timestamps sidecar file: 30 fps
+        source.seek(Duration::from_millis(70)).unwrap();
+        let frame = source.grab().unwrap();
+        assert_eq!(frame.ts, DEFAULT_FRAME_INTERVAL * 2);
+        assert_index(&frame, 2);
+
+        source.seek_frame(3).unwrap();
+        assert_index(&source.grab().unwrap(), 3);
+        assert!(source.grab().is_none());
+    }
+
+    #[test]
+    fn live_sources_are_not_seekable() {
+        let mat = cv::Mat::new(4, 4, cv::RGB::black()).unwrap();
+        let mut source = Source::new("test".into(), Kind::Image(mat));
+        assert!(!source.is_seekable());
+        assert!(source.step_back().is_err());
+    }
+}
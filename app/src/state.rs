@@ -1,53 +1,230 @@
 use crate::prelude::*;
-use std::{collections::VecDeque, time::Instant};
+use log::debug;
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    time::Duration,
+};
 
 #[derive(Debug)]
 pub struct State {
-    pub ts: Instant,
     pub cfg: Cfg,
+    /// working copy of the original frame - cropped and filtered.
+    ///
+    /// stays in image space with `Pipeline::Raw` / `Pipeline::RawMasked`
     pub frame: cv::Mat<cv::BGR>,
-    pub frame_orig: cv::Mat<cv::BGR>,
+    /// working frame before the outlines of the detections were drawn - set by `State::detect_objects`
+    pub frame_plain: Option<cv::Mat<cv::BGR>>,
+    /// original frame from the input source with its metadata
+    pub frame_orig: Frame,
+    /// pusher position in table coordinates
     pub pusher: Option<cv::Point>,
+    /// puck position in table coordinates
     pub puck: Option<cv::Point>,
     pub history: VecDeque<State>,
+    /// roi -> table transformation - reused until the roi changes
+    pub homography: Option<cv::Homography>,
+    /// background model for `DetectionMethod::MotionAndColor` - learns from frame to frame
+    pub background: Option<cv::BackgroundSubtractor>,
+    /// template matchers for `DetectionMethod::Template` - loaded once per template image
+    pub templates: HashMap<PathBuf, cv::TemplateMatcher>,
 }
 
 impl State {
-    pub fn new(cfg: Cfg, frame: cv::Mat<cv::BGR>) -> Self {
+    pub fn new(cfg: Cfg, frame: Frame) -> Self {
         Self {
-            ts: Instant::now(),
             cfg,
-            frame: frame.clone(),
+            frame: frame.mat.clone(),
+            frame_plain: None,
             frame_orig: frame,
             pusher: None,
             puck: None,
             history: Default::default(),
+            homography: None,
+            background: None,
+            templates: HashMap::new(),
         }
     }
 
-    pub fn next(mut self, frame: cv::Mat<cv::BGR>) -> Self {
+    pub fn next(mut self, frame: Frame) -> Self {
         let cfg = self.cfg.clone();
+        let homography = self.homography.take();
+        let background = self.background.take();
+        let templates = std::mem::take(&mut self.templates);
+        // the history is carried over - `puck_speed` looks up the last puck position in it
         let mut history = std::mem::take(&mut self.history);
         history.push_front(self);
         history.truncate(10);
 
         Self {
-            ts: Instant::now(),
             cfg,
-            frame: frame.clone(),
+            frame: frame.mat.clone(),
+            frame_plain: None,
             frame_orig: frame,
             pusher: None,
             puck: None,
-            history: Default::default(),
+            history,
+            homography,
+            background,
+            templates,
         }
     }
 
+    /// warps the frame to the table plane (`Pipeline::Warp`) or masks
+    /// the area outside of the roi (`Pipeline::RawMasked`).
     pub fn crop_frame(&mut self) -> Result<()> {
-        let roi = self.cfg.read()?.roi;
-        self.frame = self.frame.lens(&roi.to_array())?;
+        let pipeline = self.cfg.read()?.pipeline;
+        self.update_homography()?;
+        if let Some(homography) = &self.homography {
+            match pipeline {
+                Pipeline::Warp => self.frame = homography.warp(&self.frame)?,
+                Pipeline::Raw => (),
+                Pipeline::RawMasked => self.frame = self.frame.mask_polygon(homography.corners())?,
+            }
+        }
         Ok(())
     }
 
+    /// detects the puck and the pusher in the working frame and draws their outlines.
+    pub fn detect_objects(&mut self) -> Result<()> {
+        let cfg = self.cfg.clone();
+        let cfg = cfg.read()?;
+        let detectors = [&cfg.puck, &cfg.pusher];
+        let uses = |f: fn(&DetectionMethod) -> bool| detectors.iter().any(|d| f(&d.method));
+
+        // the inputs are prepared before the outlines are drawn
+        let frame_hsv = self.frame.convert_color();
+        let frame_gray = if uses(|m| matches!(m, DetectionMethod::HoughCircles(_))) {
+            let mut gray: cv::Mat<cv::Gray> = self.frame.convert_color();
+            gray.blur(3);
+            Some(gray)
+        } else {
+            None
+        };
+        let foreground = if uses(|m| *m == DetectionMethod::MotionAndColor) {
+            Some(self.update_background(&cfg.background)?)
+        } else {
+            None
+        };
+        for detector in &detectors {
+            if let DetectionMethod::Template(template) = &detector.method {
+                self.load_template(template)?;
+            }
+        }
+        self.frame_plain = Some(self.frame.clone());
+
+        let inputs = Inputs {
+            area_scale: area_scale(cfg.pipeline, self.homography.as_ref()),
+            hsv: &frame_hsv,
+            gray: frame_gray.as_ref(),
+            foreground: foreground.as_ref(),
+            bgr: self.frame_plain.as_ref(),
+            templates: &self.templates,
+        };
+        let puck = locate("puck", &cfg.puck, &mut self.frame, &inputs, cv::RGB::white())?;
+        let pusher = locate("pusher", &cfg.pusher, &mut self.frame, &inputs, cv::RGB::red())?;
+        drop(cfg);
+
+        self.puck = match puck {
+            Some(p) => Some(self.to_table(&p)?),
+            None => None,
+        };
+        self.pusher = match pusher {
+            Some(p) => Some(self.to_table(&p)?),
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// loads the template image - only once per path and scales, see `State::forget_template`.
+    fn load_template(&mut self, template: &Template) -> Result<()> {
+        let outdated = match self.templates.get(&template.path) {
+            Some(matcher) => matcher.scales() != template.scales.as_slice(),
+            None => true,
+        };
+        if outdated {
+            debug!("load template: {}", template.path.display());
+            let matcher = cv::TemplateMatcher::open(&template.path)?.with_scales(&template.scales);
+            self.templates.insert(template.path.clone(), matcher);
+        }
+        Ok(())
+    }
+
+    /// reloads the template image with the next detection - e.g. after a new template was captured.
+    pub fn forget_template(&mut self, template: &Template) {
+        self.templates.remove(&template.path);
+    }
+
+    /// foreground mask of the working frame - (re)creates the background model when the settings have changed.
+    fn update_background(&mut self, settings: &cv::BackgroundSettings) -> Result<cv::Mat<cv::Gray>> {
+        let outdated = match &self.background {
+            Some(background) => background.settings() != settings,
+            None => true,
+        };
+        if outdated {
+            debug!("create background model: {:?}", settings);
+            self.background = Some(cv::BackgroundSubtractor::new(settings.clone())?);
+        }
+        let background = self.background.as_mut().expect("background");
+        Ok(background.apply(&self.frame)?)
+    }
+
+    /// working frame without the outlines of the detections - e.g. to sample colors or cut templates.
+    pub fn plain_frame(&self) -> &cv::Mat<cv::BGR> {
+        self.frame_plain.as_ref().unwrap_or(&self.frame)
+    }
+
+    /// working frame in table coordinates - warped with `Pipeline::Raw` / `Pipeline::RawMasked`.
+    ///
+    /// points in this view have the same coordinates as `State::puck` and `State::pusher`.
+    pub fn table_view(&self) -> Result<cv::Mat<cv::BGR>> {
+        match (self.cfg.read()?.pipeline, &self.homography) {
+            (Pipeline::Warp, _) | (_, None) => Ok(self.frame.clone()),
+            (_, Some(homography)) => Ok(homography.warp(&self.frame)?),
+        }
+    }
+
+    /// maps the point in the working frame to table coordinates.
+    pub fn to_table(&self, p: &cv::Point) -> Result<cv::Point> {
+        match (self.cfg.read()?.pipeline, &self.homography) {
+            (Pipeline::Warp, _) | (_, None) => Ok(*p),
+            (_, Some(homography)) => Ok(homography.to_table(p)?),
+        }
+    }
+
+    /// size (width, height) of the table plane.
+    pub fn table_size(&self) -> (i32, i32) {
+        match &self.homography {
+            Some(homography) => (homography.width(), homography.height()),
+            None => (self.frame.n_cols(), self.frame.n_rows()),
+        }
+    }
+
+    /// computes the homography for the configured roi - only when the roi or the table size has changed.
+    pub fn update_homography(&mut self) -> Result<&cv::Homography> {
+        let cfg = self.cfg.read()?;
+        let (corners, calibration) = (cfg.roi.to_array(), cfg.calibration.as_ref());
+        let (width, height) = match cfg.table_size {
+            Some(size) => (size.width, size.height),
+            None => cv::Homography::size_from_corners(&corners),
+        };
+        let outdated = match &self.homography {
+            Some(homography) => !homography.matches(&corners, width, height, calibration),
+            None => true,
+        };
+        if outdated {
+            debug!(
+                "compute homography for the roi: {:?} - table size: {}x{}",
+                corners, width, height
+            );
+            let image_size = (self.frame_orig.mat.n_cols(), self.frame_orig.mat.n_rows());
+            let homography = cv::Homography::with_calibration(&corners, width, height, calibration, image_size)?;
+            self.homography = Some(homography);
+        }
+        drop(cfg);
+        Ok(self.homography.as_ref().expect("homography"))
+    }
+
     pub fn apply_filter_frame(&mut self) -> Result<()> {
         let filter = &self.cfg.read()?.filter;
         self.frame.blur(filter.blur);
@@ -61,9 +238,15 @@ impl State {
         let old_state = self.history.iter().find(|s| s.puck.is_some())?;
         let old_puck = old_state.puck?;
 
-        let dur = self.ts.duration_since(old_state.ts);
-        let dist = puck.dist(&old_puck);
-        Some(PuckSpeed::new(dur, dist))
+        PuckSpeed::between((&old_state.frame_orig, &old_puck), (&self.frame_orig, &puck))
+    }
+
+    /// distance (in pixels) and elapsed source time since the last known puck position.
+    pub fn puck_movement(&self) -> Option<(f64, Duration)> {
+        let puck = self.puck?;
+        let old_state = self.history.iter().find(|s| s.puck.is_some())?;
+        let dur = self.frame_orig.since(&old_state.frame_orig)?;
+        Some((puck.dist(&old_state.puck?), dur))
     }
 
     // pub fn draw_roi<T>(&self, frame: &mut cv::Mat<T>) {
@@ -74,3 +257,75 @@ impl State {
     //     }
     // }
 }
+
+/// ratio of the roi area in the working frame to the table area - see `Detector::with_area_scale`.
+fn area_scale(pipeline: Pipeline, homography: Option<&cv::Homography>) -> f64 {
+    match (pipeline, homography) {
+        (Pipeline::Warp, _) | (_, None) => 1.,
+        (_, Some(homography)) => {
+            let roi = cv::Polygon::from_points(homography.corners()).signed_area().abs();
+            let table = homography.width() as f64 * homography.height() as f64;
+            if table > 0. {
+                roi / table
+            } else {
+                1.
+            }
+        }
+    }
+}
+
+/// inputs of the detectors - prepared from the working frame without the outlines.
+struct Inputs<'a> {
+    /// scale of the detector area ranges - the ranges are given for the warped table
+    area_scale: f64,
+    hsv: &'a cv::Mat<cv::HSV>,
+    gray: Option<&'a cv::Mat<cv::Gray>>,
+    foreground: Option<&'a cv::Mat<cv::Gray>>,
+    bgr: Option<&'a cv::Mat<cv::BGR>>,
+    templates: &'a HashMap<PathBuf, cv::TemplateMatcher>,
+}
+
+/// position of the first detection in the working frame - draws the outlines of all detections.
+fn locate(
+    what: &str,
+    detector: &Detector,
+    frame: &mut cv::Mat<cv::BGR>,
+    inputs: &Inputs,
+    color: cv::RGB,
+) -> Result<Option<cv::Point>> {
+    let scaled;
+    let detector = if (inputs.area_scale - 1.).abs() > f64::EPSILON {
+        scaled = detector.with_area_scale(inputs.area_scale);
+        &scaled
+    } else {
+        detector
+    };
+
+    let contours = match (&detector.method, inputs.gray, inputs.foreground, inputs.bgr) {
+        (DetectionMethod::HoughCircles(params), Some(frame_gray), _, _) => {
+            let circles = detect_circles(what, params, frame_gray)?;
+            for c in &circles {
+                frame.draw_circle(&c.center.round(), c.radius.round() as i32, color, 2);
+            }
+            return Ok(circles.first().map(|c| c.center.round()));
+        }
+        (DetectionMethod::MotionAndColor, _, Some(foreground), _) => {
+            detect_moving(what, detector, inputs.hsv, foreground)?
+        }
+        (DetectionMethod::Template(template), _, _, Some(frame_bgr)) => {
+            let found = match inputs.templates.get(&template.path) {
+                Some(matcher) => detect_template(what, template, matcher, frame_bgr)?,
+                None => None,
+            };
+            if let Some(m) = &found {
+                frame.draw_rect(&m.rect, color, 2);
+                let text = format!("{:.2}", m.score);
+                frame.draw_text(&text, &cv::Point::new(m.rect.x(), m.rect.y() - 5), 0.5, color, 1);
+            }
+            return Ok(found.map(|m| m.center()));
+        }
+        _ => detect(what, detector, inputs.hsv)?,
+    };
+    frame.draw_contours(&contours, color, 2);
+    Ok(contours.iter().next().map(|c| c.center()))
+}
@@ -28,7 +28,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     loop {
         let mut image = cv::imread(&image_path)?;
         while let Ok(event) = click_events.try_recv() {
-            color = image.at(&event.point())?;
+            color = cv::HSV::from(cv::RGB::from(image.at(&event.point())?));
             println!("HSV: {}, RGB: {}", color, cv::RGB::from(color));
         }
 
@@ -0,0 +1,28 @@
+//!
+//! serves a video or image as MJPEG stream - to test network sources without a cam
+//!
+//! ```cargo run --example mjpeg-server -- <video-or-image> [addr]```
+//!
+//! and use `http://<addr>/stream.mjpg` as input source url
+//!
+use std::{env, error::Error, path::PathBuf, thread, time::Duration};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let path = PathBuf::from(
+        env::args()
+            .nth(1)
+            .ok_or("usage: mjpeg-server <video-or-image> [addr]")?,
+    );
+    let addr = env::args().nth(2).unwrap_or("127.0.0.1:8080".to_string());
+
+    let frames: Vec<_> = match cv::imread(&path) {
+        Ok(image) if !image.is_empty()? => vec![image],
+        _ => cv::VideoCapture::open_file(&path)?.collect(),
+    };
+
+    let server = cv::MjpegServer::spawn(&addr, &frames, 25.0)?;
+    println!("serve {} frames on {}", frames.len(), server.url());
+    loop {
+        thread::sleep(Duration::from_secs(60));
+    }
+}
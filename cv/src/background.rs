@@ -0,0 +1,228 @@
+//!
+//! Foreground masks from a background model - moving objects independent of their color.
+//!
+use crate::*;
+use log::debug;
+use opencv::{
+    core::Mat as OpencvMat,
+    imgproc::{threshold, THRESH_BINARY},
+    types::{PtrOfBackgroundSubtractorKNN, PtrOfBackgroundSubtractorMOG2},
+    video::{create_background_subtractor_knn, create_background_subtractor_mog2, BackgroundSubtractor as _},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Shadows are marked with this value in the foreground mask of the opencv models.
+const SHADOW_VALUE: f64 = 127.;
+
+/// Background model algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackgroundModel {
+    /// gaussian mixture model
+    Mog2,
+    /// k-nearest neighbours
+    Knn,
+}
+
+impl Default for BackgroundModel {
+    fn default() -> Self {
+        BackgroundModel::Mog2
+    }
+}
+
+/// Settings of the `BackgroundSubtractor`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundSettings {
+    pub model: BackgroundModel,
+    /// number of frames which affect the model
+    pub history: i32,
+    /// squared distance threshold between a pixel and the model - `None`: default of the model
+    /// (MOG2: 16, KNN: 400)
+    pub threshold: Option<f64>,
+    /// detect shadows - they are removed from the foreground mask
+    pub detect_shadows: bool,
+    /// how fast the model adapts: 0.0 ..= 1.0 - a negative value picks the rate from the history
+    pub learning_rate: f64,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        Self {
+            model: BackgroundModel::default(),
+            history: 500,
+            threshold: None,
+            detect_shadows: true,
+            learning_rate: -1.,
+        }
+    }
+}
+
+enum Model {
+    Mog2(PtrOfBackgroundSubtractorMOG2),
+    Knn(PtrOfBackgroundSubtractorKNN),
+}
+
+/// Learns the static background of a frame sequence - see `BackgroundSubtractor::apply`.
+///
+/// The model restarts when the frame size changes (e.g. with a new roi).
+/// A frozen model keeps the learned background, so objects which stop
+/// moving stay in the foreground.
+pub struct BackgroundSubtractor {
+    settings: BackgroundSettings,
+    model: Model,
+    frame_size: Option<(i32, i32)>,
+    frozen: bool,
+}
+
+impl BackgroundSubtractor {
+    pub fn new(settings: BackgroundSettings) -> Result<Self> {
+        let model = Self::create(&settings)?;
+        Ok(Self {
+            settings,
+            model,
+            frame_size: None,
+            frozen: false,
+        })
+    }
+
+    fn create(settings: &BackgroundSettings) -> Result<Model> {
+        let (history, shadows) = (settings.history, settings.detect_shadows);
+        Ok(match settings.model {
+            BackgroundModel::Mog2 => {
+                let threshold = settings.threshold.unwrap_or(16.);
+                Model::Mog2(create_background_subtractor_mog2(history, threshold, shadows)?)
+            }
+            BackgroundModel::Knn => {
+                let threshold = settings.threshold.unwrap_or(400.);
+                Model::Knn(create_background_subtractor_knn(history, threshold, shadows)?)
+            }
+        })
+    }
+
+    pub fn settings(&self) -> &BackgroundSettings {
+        &self.settings
+    }
+
+    /// Updates the model with the frame and returns the foreground mask (255: foreground).
+    pub fn apply<ColorSpace>(&mut self, frame: &Mat<ColorSpace>) -> Result<Mat<Gray>> {
+        let size = (frame.n_cols(), frame.n_rows());
+        if self.frame_size.map_or(false, |s| s != size) {
+            debug!("frame size changed - restart the background model");
+            self.model = Self::create(&self.settings)?;
+        }
+        self.frame_size = Some(size);
+
+        let learning_rate = self.learning_rate();
+        let mut mask = OpencvMat::default()?;
+        match &mut self.model {
+            Model::Mog2(model) => model.apply(frame.unpack(), &mut mask, learning_rate)?,
+            Model::Knn(model) => model.apply(frame.unpack(), &mut mask, learning_rate)?,
+        }
+
+        // drop the shadows
+        let mut foreground = OpencvMat::default()?;
+        threshold(&mask, &mut foreground, SHADOW_VALUE, 255., THRESH_BINARY)?;
+        Ok(Mat::pack(foreground))
+    }
+
+    /// The learned background.
+    pub fn background(&self) -> Result<Mat<BGR>> {
+        let mut background = OpencvMat::default()?;
+        match &self.model {
+            Model::Mog2(model) => model.get_background_image(&mut background)?,
+            Model::Knn(model) => model.get_background_image(&mut background)?,
+        }
+        Ok(Mat::pack(background))
+    }
+
+    /// Effective learning rate - 0 while the model is frozen.
+    pub fn learning_rate(&self) -> f64 {
+        if self.frozen {
+            0.
+        } else {
+            self.settings.learning_rate
+        }
+    }
+
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.settings.learning_rate = learning_rate.min(1.);
+    }
+
+    /// Stops / resumes learning.
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Forgets the learned background.
+    pub fn reset(&mut self) -> Result<()> {
+        self.model = Self::create(&self.settings)?;
+        self.frame_size = None;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BackgroundSubtractor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BackgroundSubtractor")
+            .field("settings", &self.settings)
+            .field("frame_size", &self.frame_size)
+            .field("frozen", &self.frozen)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_with_puck(x: i32) -> Mat<BGR> {
+        let mut frame = Mat::<BGR>::new(60, 80, RGB::black()).unwrap();
+        frame.draw_circle(&Point::new(x, 30), 8, RGB::white(), -1);
+        frame
+    }
+
+    #[test]
+    fn moving_object_in_the_foreground() {
+        let empty = Mat::<BGR>::new(60, 80, RGB::black()).unwrap();
+        for model in &[BackgroundModel::Mog2, BackgroundModel::Knn] {
+            let mut subtractor = BackgroundSubtractor::new(BackgroundSettings {
+                model: *model,
+                ..BackgroundSettings::default()
+            })
+            .unwrap();
+            for _ in 0..20 {
+                subtractor.apply(&empty).unwrap();
+            }
+            let mask = subtractor.apply(&frame_with_puck(40)).unwrap();
+            assert_eq!(mask.at(&Point::new(40, 30)).unwrap(), 255, "{:?}", model);
+            assert_eq!(mask.at(&Point::new(10, 10)).unwrap(), 0, "{:?}", model);
+        }
+    }
+
+    #[test]
+    fn frozen_model_keeps_the_background() {
+        let mut subtractor = BackgroundSubtractor::new(BackgroundSettings {
+            learning_rate: 0.5,
+            ..BackgroundSettings::default()
+        })
+        .unwrap();
+        let empty = Mat::<BGR>::new(60, 80, RGB::black()).unwrap();
+        for _ in 0..20 {
+            subtractor.apply(&empty).unwrap();
+        }
+
+        subtractor.set_frozen(true);
+        assert_eq!(subtractor.learning_rate(), 0.);
+        for _ in 0..20 {
+            let mask = subtractor.apply(&frame_with_puck(40)).unwrap();
+            assert_eq!(mask.at(&Point::new(40, 30)).unwrap(), 255);
+        }
+
+        subtractor.set_frozen(false);
+        assert_eq!(subtractor.learning_rate(), 0.5);
+    }
+}
@@ -0,0 +1,403 @@
+//!
+//! Camera intrinsics and lens distortion from chessboard views.
+//!
+//! ```no_run
+//! # use cv::*;
+//! let mut calibrator = ChessboardCalibrator::new(9, 6, 25.0);
+//! for frame in VideoCapture::open_device(0)?.take(100) {
+//!     calibrator.add_view(&frame)?;
+//! }
+//! let calibration = calibrator.calibrate()?;
+//! let undistorted = calibration.undistort(&imread("frame.png")?)?;
+//! # Ok::<(), cv::Error>(())
+//! ```
+use crate::*;
+use log::{debug, info};
+use opencv::{
+    calib3d::{
+        calibrate_camera, find_chessboard_corners, init_undistort_rectify_map, project_points, undistort,
+        undistort_points, CALIB_CB_ADAPTIVE_THRESH, CALIB_CB_FAST_CHECK, CALIB_CB_NORMALIZE_IMAGE,
+    },
+    core::{
+        gemm, Mat as OpencvMat, Point2f, Point3f, Size, TermCriteria, TermCriteria_COUNT, TermCriteria_EPS, CV_16SC2,
+        CV_64F,
+    },
+    imgproc::{corner_sub_pix, remap, INTER_LINEAR},
+    prelude::MatTrait,
+    types::{VectorOfPoint2f, VectorOfPoint3f, VectorOfVectorOfPoint2f, VectorOfVectorOfPoint3f},
+};
+use serde::{Deserialize, Serialize};
+
+/// Camera intrinsics and lens distortion coefficients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    /// Camera matrix `[fx, 0, cx, 0, fy, cy, 0, 0, 1]` (row major).
+    pub camera_matrix: [f64; 9],
+    /// Distortion coefficients `[k1, k2, p1, p2, k3]`.
+    pub distortion: Vec<f64>,
+    /// Image size of the calibration views.
+    pub width: i32,
+    pub height: i32,
+    /// RMS reprojection error in pixels.
+    pub error: f64,
+}
+
+impl Calibration {
+    fn camera_mat(&self) -> Result<OpencvMat> {
+        Ok(OpencvMat::from_exact_iter(self.camera_matrix.iter().copied())?.reshape(1, 3)?)
+    }
+
+    fn distortion_mat(&self) -> Result<OpencvMat> {
+        Ok(OpencvMat::from_exact_iter(self.distortion.iter().copied())?)
+    }
+
+    /// Removes the lens distortion from the frame.
+    pub fn undistort<ColorSpace>(&self, frame: &Mat<ColorSpace>) -> Result<Mat<ColorSpace>> {
+        let camera = self.camera_mat()?;
+        let mut undistorted = OpencvMat::default()?;
+        undistort(
+            frame.unpack(),
+            &mut undistorted,
+            &camera,
+            &self.distortion_mat()?,
+            &camera,
+        )?;
+        Ok(Mat::pack(undistorted))
+    }
+
+    /// Fails if the frames don't have the size of the calibration views - the
+    /// camera matrix (and so the undistortion) is only valid for this resolution.
+    pub fn check_image_size(&self, width: i32, height: i32) -> Result<()> {
+        if (width, height) != (self.width, self.height) {
+            return Err(Error::UserInput {
+                msg: format!(
+                    "calibration for {}x{} frames - got: {}x{}, calibrate again with this resolution",
+                    self.width, self.height, width, height
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Maps the points from the distorted to the undistorted image.
+    pub fn undistort_points(&self, ps: &[Point]) -> Result<Vec<Point>> {
+        let camera = self.camera_mat()?;
+        let mut src = VectorOfPoint2f::with_capacity(ps.len());
+        for p in ps {
+            src.push(Point2f::new(p.x as f32, p.y as f32));
+        }
+        let mut dst = VectorOfPoint2f::new();
+        undistort_points(
+            &src,
+            &mut dst,
+            &camera,
+            &self.distortion_mat()?,
+            &OpencvMat::default()?,
+            &camera,
+        )?;
+        Ok(dst.iter().map(|p| round(&p)).collect())
+    }
+
+    /// Maps the points from the undistorted to the distorted image.
+    pub fn distort_points(&self, ps: &[Point]) -> Result<Vec<Point>> {
+        let k = &self.camera_matrix;
+        let (fx, cx, fy, cy) = (k[0], k[2], k[4], k[5]);
+        // back to normalized camera coordinates - projected with the distortion
+        let mut object = VectorOfPoint3f::with_capacity(ps.len());
+        for p in ps {
+            let x = (p.x as f64 - cx) / fx;
+            let y = (p.y as f64 - cy) / fy;
+            object.push(Point3f::new(x as f32, y as f32, 1.));
+        }
+        let zero = OpencvMat::new_rows_cols_with_default(3, 1, CV_64F, opencv::core::Scalar::all(0.))?;
+        let mut dst = VectorOfPoint2f::new();
+        project_points(
+            &object,
+            &zero,
+            &zero,
+            &self.camera_mat()?,
+            &self.distortion_mat()?,
+            &mut dst,
+            &mut OpencvMat::default()?,
+            0.,
+        )?;
+        Ok(dst.iter().map(|p| round(&p)).collect())
+    }
+
+    /// Lookup maps for `remap`, which undistort the frame and apply the homography
+    /// (undistorted image -> table) in a single step.
+    pub(crate) fn rectify_maps(
+        &self,
+        homography: &OpencvMat,
+        width: i32,
+        height: i32,
+    ) -> Result<(OpencvMat, OpencvMat)> {
+        let camera = self.camera_mat()?;
+        // `init_undistort_rectify_map` maps the target pixels with the inverse of `new_camera * R`,
+        // so the homography is folded into the new camera matrix
+        let mut new_camera = OpencvMat::default()?;
+        gemm(homography, &camera, 1., &OpencvMat::default()?, 0., &mut new_camera, 0)?;
+        let (mut map1, mut map2) = (OpencvMat::default()?, OpencvMat::default()?);
+        init_undistort_rectify_map(
+            &camera,
+            &self.distortion_mat()?,
+            &OpencvMat::default()?,
+            &new_camera,
+            Size::new(width, height),
+            CV_16SC2,
+            &mut map1,
+            &mut map2,
+        )?;
+        Ok((map1, map2))
+    }
+}
+
+/// Remaps the frame with the maps from `Calibration::rectify_maps`.
+pub(crate) fn remap_frame<ColorSpace>(
+    frame: &Mat<ColorSpace>,
+    maps: &(OpencvMat, OpencvMat),
+) -> Result<Mat<ColorSpace>> {
+    let mut remapped = OpencvMat::default()?;
+    remap(
+        frame.unpack(),
+        &mut remapped,
+        &maps.0,
+        &maps.1,
+        INTER_LINEAR,
+        opencv::core::BORDER_CONSTANT,
+        opencv::core::Scalar::default(),
+    )?;
+    Ok(Mat::pack(remapped))
+}
+
+fn round(p: &Point2f) -> Point {
+    Point::new(p.x.round() as i32, p.y.round() as i32)
+}
+
+/// Chessboard corners found in a frame - see `ChessboardCalibrator::find_view`.
+pub struct ChessboardView {
+    /// sub-pixel corners
+    corners: VectorOfPoint2f,
+    /// size of the frame
+    size: Size,
+}
+
+impl ChessboardView {
+    /// The corners rounded to pixels - e.g. to draw them.
+    pub fn points(&self) -> Vec<Point> {
+        self.corners.iter().map(|p| round(&p)).collect()
+    }
+}
+
+/// Collects chessboard views and estimates the `Calibration`.
+///
+/// Use views from different angles and distances, which cover the whole image -
+/// especially the edges, where the distortion is the strongest.
+pub struct ChessboardCalibrator {
+    /// number of inner corners per row / column
+    pattern: Size,
+    /// edge length of a square - e.g. in millimeters
+    square_size: f32,
+    views: VectorOfVectorOfPoint2f,
+    image_size: Option<Size>,
+}
+
+impl ChessboardCalibrator {
+    /// Calibrator for a chessboard with `cols` x `rows` inner corners.
+    pub fn new(cols: i32, rows: i32, square_size: f32) -> ChessboardCalibrator {
+        ChessboardCalibrator {
+            pattern: Size::new(cols, rows),
+            square_size,
+            views: VectorOfVectorOfPoint2f::new(),
+            image_size: None,
+        }
+    }
+
+    /// Number of collected views.
+    pub fn len(&self) -> usize {
+        self.views.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+
+    /// Finds the chessboard in the frame - `None` if the chessboard is not fully visible.
+    ///
+    /// Keep the view with `ChessboardCalibrator::add` - without a second detection.
+    pub fn find_view(&self, frame: &Mat<BGR>) -> Result<Option<ChessboardView>> {
+        let size = Size::new(frame.n_cols(), frame.n_rows());
+        Ok(self.find(frame)?.map(|corners| ChessboardView { corners, size }))
+    }
+
+    /// Keeps the view for the calibration - all views need the same image size.
+    pub fn add(&mut self, view: ChessboardView) -> Result<()> {
+        if let Some(expected) = self.image_size {
+            if expected != view.size {
+                return Err(Error::UserInput {
+                    msg: format!(
+                        "all views need the same size - expected: {}x{}, got: {}x{}",
+                        expected.width, expected.height, view.size.width, view.size.height
+                    ),
+                });
+            }
+        }
+        self.views.push(view.corners);
+        self.image_size = Some(view.size);
+        debug!("chessboard view {} added", self.views.len());
+        Ok(())
+    }
+
+    /// Finds the chessboard corners and keeps them as view for the calibration.
+    ///
+    /// Returns the corners of the view, or `None` if no chessboard was found.
+    pub fn add_view(&mut self, frame: &Mat<BGR>) -> Result<Option<Vec<Point>>> {
+        match self.find_view(frame)? {
+            Some(view) => {
+                let points = view.points();
+                self.add(view)?;
+                Ok(Some(points))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn find(&self, frame: &Mat<BGR>) -> Result<Option<VectorOfPoint2f>> {
+        let gray: Mat<Gray> = frame.convert_color();
+
+        let mut corners = VectorOfPoint2f::new();
+        let flags = CALIB_CB_ADAPTIVE_THRESH + CALIB_CB_NORMALIZE_IMAGE + CALIB_CB_FAST_CHECK;
+        if !find_chessboard_corners(gray.unpack(), self.pattern, &mut corners, flags)? {
+            return Ok(None);
+        }
+
+        let criteria = TermCriteria::new(TermCriteria_COUNT + TermCriteria_EPS, 30, 0.001)?;
+        corner_sub_pix(
+            gray.unpack(),
+            &mut corners,
+            Size::new(11, 11),
+            Size::new(-1, -1),
+            criteria,
+        )?;
+        Ok(Some(corners))
+    }
+
+    /// The chessboard corners in the chessboard plane (z = 0).
+    fn board(&self) -> VectorOfPoint3f {
+        let mut board = VectorOfPoint3f::new();
+        for row in 0..self.pattern.height {
+            for col in 0..self.pattern.width {
+                board.push(Point3f::new(
+                    col as f32 * self.square_size,
+                    row as f32 * self.square_size,
+                    0.,
+                ));
+            }
+        }
+        board
+    }
+
+    /// Estimates the intrinsics and the distortion from the collected views.
+    pub fn calibrate(&self) -> Result<Calibration> {
+        let image_size = match self.image_size {
+            Some(size) if self.views.len() >= 3 => size,
+            _ => {
+                return Err(Error::UserInput {
+                    msg: format!("calibration needs at least 3 views - got: {}", self.views.len()),
+                })
+            }
+        };
+
+        let mut object_points = VectorOfVectorOfPoint3f::new();
+        for _ in 0..self.views.len() {
+            object_points.push(self.board());
+        }
+
+        let mut camera = OpencvMat::default()?;
+        let mut distortion = OpencvMat::default()?;
+        let criteria = TermCriteria::new(TermCriteria_COUNT + TermCriteria_EPS, 30, f64::EPSILON)?;
+        let error = calibrate_camera(
+            &object_points,
+            &self.views,
+            image_size,
+            &mut camera,
+            &mut distortion,
+            &mut OpencvMat::default()?,
+            &mut OpencvMat::default()?,
+            0,
+            criteria,
+        )?;
+
+        let mut camera_matrix = [0.; 9];
+        for (i, value) in camera_matrix.iter_mut().enumerate() {
+            *value = *camera.at_2d::<f64>(i as i32 / 3, i as i32 % 3)?;
+        }
+        let distortion = (0..distortion.total()? as i32)
+            .map(|i| distortion.at::<f64>(i).map(|v| *v))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        info!(
+            "calibration from {} views - reprojection error: {:.3}px",
+            self.views.len(),
+            error
+        );
+        Ok(Calibration {
+            camera_matrix,
+            distortion,
+            width: image_size.width,
+            height: image_size.height,
+            error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calibration() -> Calibration {
+        Calibration {
+            camera_matrix: [500., 0., 320., 0., 500., 240., 0., 0., 1.],
+            distortion: vec![-0.2, 0.05, 0., 0., 0.],
+            width: 640,
+            height: 480,
+            error: 0.3,
+        }
+    }
+
+    #[test]
+    fn undistort_distort_points_round_trip() {
+        let calibration = calibration();
+        let points = vec![
+            Point::new(320, 240),
+            Point::new(40, 30),
+            Point::new(600, 60),
+            Point::new(100, 400),
+        ];
+
+        let undistorted = calibration.undistort_points(&points).unwrap();
+        // the center stays - the barrel distortion moves the edges outwards when it is removed
+        assert_eq!(undistorted[0], Point::new(320, 240));
+        assert!(undistorted[1].x() < 40 && undistorted[1].y() < 30);
+
+        let distorted = calibration.distort_points(&undistorted).unwrap();
+        for (p, expected) in distorted.iter().zip(&points) {
+            assert!(p.dist(expected) <= 1.5, "{:?} != {:?}", p, expected);
+        }
+    }
+
+    #[test]
+    fn check_image_size() {
+        let calibration = calibration();
+        assert!(calibration.check_image_size(640, 480).is_ok());
+        assert!(calibration.check_image_size(1280, 720).is_err());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let calibration = calibration();
+        let json = serde_json::to_string(&calibration).unwrap();
+        let parsed: Calibration = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, calibration);
+    }
+}
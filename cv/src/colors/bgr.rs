@@ -2,7 +2,7 @@ use crate::*;
 use opencv::core::{Scalar as OpencvScalar, Vec3b};
 
 /// Represents a BGR color
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct BGR {
     pub(crate) b: u8,
     pub(crate) g: u8,
@@ -0,0 +1,180 @@
+use crate::*;
+use std::f64::consts::PI;
+
+/// Lower / upper percentile for the fitted ranges - ignores outliers like reflections.
+const LOW_PERCENTILE: f64 = 0.05;
+const HIGH_PERCENTILE: f64 = 0.95;
+
+/// Extra margin for the fitted offsets.
+const MARGIN: i32 = 5;
+
+/// Low / median / high percentiles of a channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percentiles {
+    pub low: u8,
+    pub median: u8,
+    pub high: u8,
+}
+
+impl Percentiles {
+    fn from_sorted(sorted: &[i32]) -> Percentiles {
+        let at = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+        Percentiles {
+            low: at(LOW_PERCENTILE) as u8,
+            median: at(0.5) as u8,
+            high: at(HIGH_PERCENTILE) as u8,
+        }
+    }
+}
+
+/// Robust statistics of sampled HSV colors.
+///
+/// The hue is circular (0 - 179), so its statistics are relative to the
+/// circular mean - a red sample around `h: 0` gives a narrow range
+/// which wraps around 0, and not the whole hue circle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorStats {
+    /// number of samples
+    pub count: usize,
+    /// circular mean of the hue
+    pub hue_mean: u8,
+    /// circular standard deviation of the hue
+    pub hue_deviation: f64,
+    /// hue percentiles - `low > high` if the range wraps around 0
+    pub hue: Percentiles,
+    pub saturation: Percentiles,
+    pub value: Percentiles,
+}
+
+impl ColorStats {
+    /// Statistics of the samples - `None` without samples.
+    pub fn from_samples(samples: &[HSV]) -> Option<ColorStats> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        // hue: 0 - 179 -> angle: 0 - 2pi
+        let to_angle = |h: u8| h as f64 * PI / 90.;
+        let (sin, cos) = samples.iter().fold((0., 0.), |(sin, cos), hsv| {
+            let angle = to_angle(hsv.h);
+            (sin + angle.sin(), cos + angle.cos())
+        });
+        let n = samples.len() as f64;
+        let mean_angle = sin.atan2(cos).rem_euclid(2. * PI);
+        let resultant = ((sin / n).powi(2) + (cos / n).powi(2)).sqrt().min(1.);
+        let hue_deviation = (-2. * resultant.max(f64::MIN_POSITIVE).ln()).sqrt() * 90. / PI;
+        let hue_mean = (mean_angle * 90. / PI).round() as i32 % 180;
+
+        // hue differences to the mean: -90 ..= 89
+        let mut hue_diffs: Vec<i32> = samples
+            .iter()
+            .map(|hsv| (hsv.h as i32 - hue_mean + 90).rem_euclid(180) - 90)
+            .collect();
+        hue_diffs.sort();
+        let diffs = Percentiles::from_sorted(&hue_diffs.iter().map(|d| d + 90).collect::<Vec<_>>());
+        let wrap = |d: u8| (hue_mean + d as i32 - 90).rem_euclid(180) as u8;
+        let hue = Percentiles {
+            low: wrap(diffs.low),
+            median: wrap(diffs.median),
+            high: wrap(diffs.high),
+        };
+
+        let sorted = |channel: fn(&HSV) -> u8| {
+            let mut values: Vec<i32> = samples.iter().map(|hsv| channel(hsv) as i32).collect();
+            values.sort();
+            Percentiles::from_sorted(&values)
+        };
+
+        Some(ColorStats {
+            count: samples.len(),
+            hue_mean: hue_mean as u8,
+            hue_deviation,
+            hue,
+            saturation: sorted(|hsv| hsv.s),
+            value: sorted(|hsv| hsv.v),
+        })
+    }
+
+    /// Statistics of the colors in the rect - clipped to the frame.
+    pub fn from_region(frame: &Mat<HSV>, rect: &Rect) -> Result<Option<ColorStats>> {
+        Ok(ColorStats::from_samples(&frame.region(rect)?))
+    }
+
+    /// Statistics of the colors around the points - see `ColorStats::from_region`.
+    pub fn from_points(frame: &Mat<HSV>, points: &[Point], radius: i32) -> Result<Option<ColorStats>> {
+        let size = radius * 2 + 1;
+        let mut samples = Vec::new();
+        for p in points {
+            samples.extend(frame.region(&Rect::center(p, size, size))?);
+        }
+        Ok(ColorStats::from_samples(&samples))
+    }
+
+    /// Color and offsets (hue, saturation, value) which cover the samples between the percentiles.
+    pub fn fit(&self) -> (HSV, (i32, i32, i32)) {
+        let center = |p: &Percentiles| (p.low as i32 + p.high as i32 + 1) / 2;
+        let offset = |p: &Percentiles| (p.high as i32 - p.low as i32 + 1) / 2 + MARGIN;
+
+        // the hue percentiles are relative to the mean
+        let lo = (self.hue.low as i32 - self.hue_mean as i32 + 90).rem_euclid(180) - 90;
+        let hi = (self.hue.high as i32 - self.hue_mean as i32 + 90).rem_euclid(180) - 90;
+        let h = (self.hue_mean as i32 + (lo + hi) / 2).rem_euclid(180);
+        let h_offset = (hi - lo + 1) / 2 + MARGIN;
+
+        let color = HSV::unsafe_new(h as u8, center(&self.saturation) as u8, center(&self.value) as u8);
+        (color, (h_offset, offset(&self.saturation), offset(&self.value)))
+    }
+
+    /// The range from `ColorStats::fit`.
+    pub fn range(&self) -> Result<HSVRange> {
+        let (color, offsets) = self.fit();
+        HSVRange::from_hsv(&color, offsets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hue_statistics_around_zero() {
+        let samples: Vec<HSV> = [176, 178, 179, 0, 1, 2, 4]
+            .iter()
+            .map(|h| HSV::unsafe_new(*h, 200, 150))
+            .collect();
+        let stats = ColorStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.hue_mean, 0);
+        assert_eq!(stats.hue.median, 0);
+        assert_eq!((stats.hue.low, stats.hue.high), (176, 4));
+        assert!(stats.hue_deviation < 5.);
+
+        let (color, (h_offset, s_offset, v_offset)) = stats.fit();
+        assert_eq!(color, HSV::unsafe_new(0, 200, 150));
+        assert_eq!((h_offset, s_offset, v_offset), (9, 5, 5));
+
+        let range = stats.range().unwrap();
+        assert!(range.is_wrapping());
+        for hsv in &samples {
+            assert!(range.contains(hsv), "{}", hsv);
+        }
+    }
+
+    #[test]
+    fn percentiles_ignore_outliers() {
+        let mut samples: Vec<HSV> = (0..100)
+            .map(|i| HSV::unsafe_new(60, 100 + (i % 11) as u8, 120))
+            .collect();
+        // reflections
+        samples.push(HSV::unsafe_new(60, 0, 255));
+        samples.push(HSV::unsafe_new(60, 0, 255));
+        let stats = ColorStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.hue_mean, 60);
+        assert_eq!(stats.saturation.median, 105);
+        assert_eq!(stats.value.high, 120);
+    }
+
+    #[test]
+    fn no_samples() {
+        assert_eq!(ColorStats::from_samples(&[]), None);
+    }
+}
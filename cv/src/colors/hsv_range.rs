@@ -2,6 +2,10 @@ use crate::*;
 use serde::{Deserialize, Serialize};
 use std::{fmt, ops::RangeInclusive};
 
+/// Range of HSV colors.
+///
+/// The hue is circular (0 - 179): if `min.h > max.h`, the hue range wraps
+/// around 0 and covers `min.h ..= 179` and `0 ..= max.h` - e.g. for red.
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub struct HSVRange {
     min: HSV,
@@ -9,6 +13,7 @@ pub struct HSVRange {
 }
 
 impl HSVRange {
+    /// A hue range with `start > end` wraps around 0 - e.g. `170..=10`.
     pub fn new(h_range: RangeInclusive<u8>, s_range: RangeInclusive<u8>, v_range: RangeInclusive<u8>) -> Result<Self> {
         Ok(HSVRange {
             min: HSV::new(*h_range.start(), *s_range.start(), *v_range.start())?,
@@ -16,9 +21,20 @@ impl HSVRange {
         })
     }
 
+    /// Range around the color - the hue range wraps around 0, saturation and value are clamped.
     pub fn from_hsv(hsv: &HSV, offsets: (i32, i32, i32)) -> Result<Self> {
-        let min = hsv.adjust((-offsets.0, -offsets.1, -offsets.2));
-        let max = hsv.adjust(offsets);
+        let mut min = hsv.adjust((0, -offsets.1, -offsets.2));
+        let mut max = hsv.adjust((0, offsets.1, offsets.2));
+
+        let h_offset = offsets.0.abs();
+        if h_offset >= 90 {
+            // covers the whole hue circle
+            min.h = 0;
+            max.h = 179;
+        } else {
+            min.h = (hsv.h as i32 - h_offset).rem_euclid(180) as u8;
+            max.h = (hsv.h as i32 + h_offset).rem_euclid(180) as u8;
+        }
 
         Ok(HSVRange { min, max })
     }
@@ -30,11 +46,47 @@ impl HSVRange {
     pub fn max(&self) -> &HSV {
         &self.max
     }
+
+    /// `true` if the hue range wraps around 0.
+    pub fn is_wrapping(&self) -> bool {
+        self.min.h > self.max.h
+    }
+
+    /// Splits a wrapping range at the hue boundary into `min.h ..= 179` and `0 ..= max.h`.
+    ///
+    /// Ranges which don't wrap are returned unchanged.
+    pub fn split(&self) -> (HSVRange, Option<HSVRange>) {
+        if !self.is_wrapping() {
+            return (*self, None);
+        }
+        let upper = HSVRange {
+            min: self.min,
+            max: HSV::unsafe_new(179, self.max.s, self.max.v),
+        };
+        let lower = HSVRange {
+            min: HSV::unsafe_new(0, self.min.s, self.min.v),
+            max: self.max,
+        };
+        (upper, Some(lower))
+    }
+
+    pub fn contains(&self, hsv: &HSV) -> bool {
+        let h = if self.is_wrapping() {
+            hsv.h >= self.min.h || hsv.h <= self.max.h
+        } else {
+            hsv.h >= self.min.h && hsv.h <= self.max.h
+        };
+        h && (self.min.s..=self.max.s).contains(&hsv.s) && (self.min.v..=self.max.v).contains(&hsv.v)
+    }
 }
 
 impl fmt::Display for HSVRange {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} -> {}", self.min(), self.max())
+        write!(f, "{} -> {}", self.min(), self.max())?;
+        if self.is_wrapping() {
+            write!(f, " (hue wraps)")?;
+        }
+        Ok(())
     }
 }
 
@@ -48,4 +100,68 @@ mod tests {
         let range = HSVRange::from_hsv(&hsv, (10, 20, 30)).unwrap();
         assert_eq!(range, HSVRange::new(30..=50, 40..=80, 50..=110).unwrap());
     }
+
+    #[test]
+    fn from_hsv_wraps_below_zero() {
+        let red = HSV::unsafe_new(0, 220, 140);
+        let range = HSVRange::from_hsv(&red, (30, 43, 35)).unwrap();
+        assert!(range.is_wrapping());
+        assert_eq!(range, HSVRange::new(150..=30, 177..=255, 105..=175).unwrap());
+    }
+
+    #[test]
+    fn from_hsv_wraps_above_179() {
+        let range = HSVRange::from_hsv(&HSV::unsafe_new(175, 100, 100), (10, 0, 0)).unwrap();
+        assert_eq!(range, HSVRange::new(165..=5, 100..=100, 100..=100).unwrap());
+    }
+
+    #[test]
+    fn from_hsv_full_hue_circle() {
+        let range = HSVRange::from_hsv(&HSV::unsafe_new(10, 100, 100), (90, 10, 10)).unwrap();
+        assert!(!range.is_wrapping());
+        assert_eq!(range, HSVRange::new(0..=179, 90..=110, 90..=110).unwrap());
+    }
+
+    #[test]
+    fn contains_at_hue_boundary() {
+        let range = HSVRange::new(170..=10, 0..=255, 0..=255).unwrap();
+        for h in &[170, 175, 179, 0, 5, 10] {
+            assert!(range.contains(&HSV::unsafe_new(*h, 100, 100)), "hue: {}", h);
+        }
+        for h in &[11, 90, 169] {
+            assert!(!range.contains(&HSV::unsafe_new(*h, 100, 100)), "hue: {}", h);
+        }
+    }
+
+    #[test]
+    fn split_wrapping_range() {
+        let range = HSVRange::new(170..=10, 20..=200, 30..=220).unwrap();
+        let (upper, lower) = range.split();
+        assert_eq!(upper, HSVRange::new(170..=179, 20..=200, 30..=220).unwrap());
+        assert_eq!(lower, Some(HSVRange::new(0..=10, 20..=200, 30..=220).unwrap()));
+
+        let range = HSVRange::new(30..=50, 20..=200, 30..=220).unwrap();
+        assert_eq!(range.split(), (range, None));
+    }
+
+    #[test]
+    fn in_range_combines_wrapping_masks() {
+        use opencv::prelude::MatTrait;
+
+        let range = HSVRange::from_hsv(&HSV::unsafe_new(0, 200, 200), (10, 50, 50)).unwrap();
+        for (h, expected) in &[
+            (0, 255),
+            (5, 255),
+            (10, 255),
+            (170, 255),
+            (179, 255),
+            (11, 0),
+            (90, 0),
+            (169, 0),
+        ] {
+            let pixel = Mat::<HSV>::new(1, 1, HSV::unsafe_new(*h, 200, 200)).unwrap();
+            let mask = pixel.in_range(&range);
+            assert_eq!(*mask.unpack().at_2d::<u8>(0, 0).unwrap(), *expected, "hue: {}", h);
+        }
+    }
 }
@@ -0,0 +1,31 @@
+use crate::*;
+use opencv::core::Scalar as OpencvScalar;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Represents a CIE L*a*b* color - 8 bit OpenCV scaling:
+/// `l`: 0 - 255 (lightness), `a` / `b`: 0 - 255 with 128 as neutral.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Lab {
+    pub(crate) l: u8,
+    pub(crate) a: u8,
+    pub(crate) b: u8,
+}
+
+impl Lab {
+    pub fn new(l: u8, a: u8, b: u8) -> Lab {
+        Lab { l, a, b }
+    }
+}
+
+impl ToOpencvScalar for Lab {
+    fn to_opencv_scalar(&self) -> OpencvScalar {
+        OpencvScalar::new(self.l as f64, self.a as f64, self.b as f64, 0.0)
+    }
+}
+
+impl fmt::Display for Lab {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}, {}", self.l, self.a, self.b)
+    }
+}
@@ -1,14 +1,21 @@
 use opencv::core::Scalar as OpencvScalar;
 
 mod bgr;
+mod color_stats;
 mod hsv;
 mod hsv_range;
+mod lab;
 mod rgb;
+mod ycrcb;
 pub use bgr::*;
+pub use color_stats::*;
 pub use hsv::*;
 pub use hsv_range::*;
+pub use lab::*;
 pub use rgb::*;
+pub use ycrcb::*;
 
+/// Single channel (8 bit) color space - the pixel value is a `u8`.
 #[derive(Debug)]
 pub struct Gray;
 
@@ -0,0 +1,30 @@
+use crate::*;
+use opencv::core::Scalar as OpencvScalar;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Represents a YCrCb color - `y`: luma, `cr` / `cb`: chroma with 128 as neutral.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct YCrCb {
+    pub(crate) y: u8,
+    pub(crate) cr: u8,
+    pub(crate) cb: u8,
+}
+
+impl YCrCb {
+    pub fn new(y: u8, cr: u8, cb: u8) -> YCrCb {
+        YCrCb { y, cr, cb }
+    }
+}
+
+impl ToOpencvScalar for YCrCb {
+    fn to_opencv_scalar(&self) -> OpencvScalar {
+        OpencvScalar::new(self.y as f64, self.cr as f64, self.cb as f64, 0.0)
+    }
+}
+
+impl fmt::Display for YCrCb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}, {}", self.y, self.cr, self.cb)
+    }
+}
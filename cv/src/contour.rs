@@ -2,9 +2,40 @@ use crate::*;
 use std::iter::{self, IntoIterator};
 
 use opencv::{
+    core::{Point2f as OpencvPoint2f, RotatedRect},
+    imgproc::Moments,
     prelude::Vector,
     types::{VectorOfPoint, VectorOfVectorOfPoint},
 };
+use std::f64::consts::PI;
+
+/// Circle with a sub-pixel center.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Circle {
+    pub center: Point2f,
+    pub radius: f64,
+}
+
+/// Ellipse with a sub-pixel center.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Ellipse {
+    pub center: Point2f,
+    /// full lengths of the axes
+    pub axes: (f64, f64),
+    /// rotation in degrees
+    pub angle: f64,
+}
+
+impl Ellipse {
+    fn pack(rect: &RotatedRect) -> Result<Self> {
+        let (center, size) = (rect.center()?, rect.size()?);
+        Ok(Ellipse {
+            center: Point2f::new(center.x as f64, center.y as f64),
+            axes: (size.width as f64, size.height as f64),
+            angle: rect.angle()? as f64,
+        })
+    }
+}
 
 pub struct Contour(VectorOfPoint);
 impl Contour {
@@ -16,6 +47,10 @@ impl Contour {
         &self.0
     }
 
+    pub fn from_points(points: &[Point]) -> Self {
+        Contour::pack(Vector::from_iter(points.iter().map(Point::unpack)))
+    }
+
     pub fn points(&self) -> Vec<Point> {
         self.0.iter().map(Point::pack).collect()
     }
@@ -43,10 +78,80 @@ impl Contour {
         Contour::pack(out)
     }
 
+    pub fn convex_hull(&self) -> Self {
+        let mut hull = VectorOfPoint::new();
+        let clockwise = false;
+        let return_points = true;
+        opencv::imgproc::convex_hull(self.unpack(), &mut hull, clockwise, return_points).expect("convex_hull");
+        Contour::pack(hull)
+    }
+
+    pub fn is_convex(&self) -> bool {
+        opencv::imgproc::is_contour_convex(self.unpack()).expect("is_contour_convex")
+    }
+
+    /// Midpoint of the bounding rect - see `Contour::centroid` for the sub-pixel center of mass.
     pub fn center(&self) -> Point {
         let r = self.bounding_rect();
         Point::new(r.x + (r.width / 2), r.y + (r.height / 2))
     }
+
+    fn moments(&self) -> Moments {
+        let binary_image = false;
+        opencv::imgproc::moments(self.unpack(), binary_image).expect("moments")
+    }
+
+    /// Center of mass of the enclosed area - `None` for a contour without area.
+    pub fn centroid(&self) -> Option<Point2f> {
+        centroid(&self.moments())
+    }
+
+    /// `4 * PI * area / perimeter^2` - 1 for a circle, ~0.785 for a square, towards 0 for thin shapes.
+    pub fn circularity(&self) -> f64 {
+        let perimeter = self.arc_length(true);
+        if perimeter > 0. {
+            4. * PI * self.area() / perimeter.powi(2)
+        } else {
+            0.
+        }
+    }
+
+    pub fn min_enclosing_circle(&self) -> Circle {
+        let mut center = OpencvPoint2f::default();
+        let mut radius = 0.;
+        opencv::imgproc::min_enclosing_circle(self.unpack(), &mut center, &mut radius).expect("min_enclosing_circle");
+        Circle {
+            center: Point2f::new(center.x as f64, center.y as f64),
+            radius: radius as f64,
+        }
+    }
+
+    /// Ellipse fitted to the contour points - needs at least 5 points.
+    pub fn fit_ellipse(&self) -> Option<Ellipse> {
+        if self.len() < 5 {
+            return None;
+        }
+        let rect = opencv::imgproc::fit_ellipse(self.unpack()).expect("fit_ellipse");
+        Some(Ellipse::pack(&rect).expect("fit_ellipse"))
+    }
+
+    /// Ratio of the contour area to the area of its convex hull - 1 for convex shapes.
+    pub fn solidity(&self) -> f64 {
+        let hull = self.convex_hull().area();
+        if hull > 0. {
+            self.area() / hull
+        } else {
+            0.
+        }
+    }
+}
+
+pub(crate) fn centroid(moments: &Moments) -> Option<Point2f> {
+    if moments.m00.abs() > f64::EPSILON {
+        Some(Point2f::new(moments.m10 / moments.m00, moments.m01 / moments.m00))
+    } else {
+        None
+    }
 }
 
 pub struct Contours(VectorOfVectorOfPoint);
@@ -81,3 +186,50 @@ impl iter::FromIterator<Contour> for Contours {
         Contours::pack(vec_points)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: i32, y: i32, size: i32) -> Contour {
+        Contour::from_points(&[
+            Point::new(x, y),
+            Point::new(x + size, y),
+            Point::new(x + size, y + size),
+            Point::new(x, y + size),
+        ])
+    }
+
+    #[test]
+    fn sub_pixel_centroid() {
+        let centroid = square(10, 20, 5).centroid().unwrap();
+        assert!(centroid.dist(&Point2f::new(12.5, 22.5)) < 1e-9);
+
+        let line = Contour::from_points(&[Point::new(0, 0), Point::new(10, 0)]);
+        assert_eq!(line.centroid(), None);
+    }
+
+    #[test]
+    fn shape_descriptors() {
+        let square = square(0, 0, 10);
+        assert!((square.circularity() - PI / 4.).abs() < 1e-9);
+        assert!((square.solidity() - 1.).abs() < 1e-9);
+
+        let circle = square.min_enclosing_circle();
+        assert!(circle.center.dist(&Point2f::new(5., 5.)) < 0.1);
+        assert!((circle.radius - 50_f64.sqrt()).abs() < 0.1);
+
+        // L-shape: 3 of the 4 quadrants of the hull are covered
+        let l_shape = Contour::from_points(&[
+            Point::new(0, 0),
+            Point::new(10, 0),
+            Point::new(10, 5),
+            Point::new(5, 5),
+            Point::new(5, 10),
+            Point::new(0, 10),
+        ]);
+        assert!((l_shape.solidity() - 75. / 87.5).abs() < 1e-9);
+        assert!(l_shape.fit_ellipse().is_some());
+        assert_eq!(square.fit_ellipse(), None);
+    }
+}
@@ -14,6 +14,9 @@ pub enum Error {
 
     #[snafu(display("User input error: {}", msg))]
     UserInput { msg: String },
+
+    #[snafu(display("IO error: {}", source))]
+    IO { source: std::io::Error },
 }
 
 impl From<opencv::Error> for Error {
@@ -21,3 +24,9 @@ impl From<opencv::Error> for Error {
         Error::OpenCV { source }
     }
 }
+
+impl From<std::io::Error> for Error {
+    fn from(source: std::io::Error) -> Self {
+        Error::IO { source }
+    }
+}
@@ -0,0 +1,369 @@
+//!
+//! Segments, rays and polygons in sub-pixel (`Vec2`) coordinates.
+//!
+//! Everything is computed with vectors, so vertical lines need no special cases.
+//!
+//! ```
+//! # use cv::*;
+//! let table = Polygon::from_points(&[
+//!     Point::new(0, 0),
+//!     Point::new(100, 0),
+//!     Point::new(100, 200),
+//!     Point::new(0, 200),
+//! ]);
+//! // puck with a radius of 5 moving to the right wall
+//! let ray = Ray::new(Vec2::new(50., 100.), Vec2::new(1., 1.));
+//! let hit = table.sweep_circle(&ray, 5.).unwrap();
+//! assert_eq!(hit.center.round(), Point::new(95, 145));
+//! assert_eq!(hit.reflect(&ray.dir()).round(), Point::new(-1, 1));
+//! ```
+use crate::*;
+
+const EPSILON: f64 = 1e-9;
+
+/// Mirrors the vector about a line with the given unit normal.
+pub fn reflect(v: &Vec2, normal: &Vec2) -> Vec2 {
+    *v - *normal * (2. * v.dot(normal))
+}
+
+/// Line segment between `a` and `b`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Segment {
+    pub(crate) a: Vec2,
+    pub(crate) b: Vec2,
+}
+
+impl Segment {
+    pub fn new(a: Vec2, b: Vec2) -> Self {
+        Segment { a, b }
+    }
+
+    pub fn from_points(a: &Point, b: &Point) -> Self {
+        Segment::new(Vec2::from(a), Vec2::from(b))
+    }
+
+    pub fn a(&self) -> Vec2 {
+        self.a
+    }
+
+    pub fn b(&self) -> Vec2 {
+        self.b
+    }
+
+    /// Vector from `a` to `b`.
+    pub fn direction(&self) -> Vec2 {
+        self.b - self.a
+    }
+
+    pub fn length(&self) -> f64 {
+        self.direction().norm()
+    }
+
+    /// Unit normal - rotated by 90 degrees from the direction. `None` for a segment without length.
+    pub fn normal(&self) -> Option<Vec2> {
+        self.direction().perp().normalize()
+    }
+
+    /// Point on the segment with the smallest distance to `p`.
+    pub fn closest_point(&self, p: &Vec2) -> Vec2 {
+        let dir = self.direction();
+        let len2 = dir.dot(&dir);
+        if len2 < EPSILON {
+            return self.a;
+        }
+        let t = ((*p - self.a).dot(&dir) / len2).max(0.).min(1.);
+        self.a + dir * t
+    }
+
+    pub fn distance(&self, p: &Vec2) -> f64 {
+        self.closest_point(p).dist(p)
+    }
+
+    /// Intersection with the other segment - `None` if they don't cross or are parallel.
+    pub fn intersection(&self, other: &Segment) -> Option<Vec2> {
+        let (t, u) = intersect(self.a, self.direction(), other)?;
+        if t >= -EPSILON && t <= 1. + EPSILON && u >= -EPSILON && u <= 1. + EPSILON {
+            Some(self.a + self.direction() * t)
+        } else {
+            None
+        }
+    }
+
+    /// Mirrors the vector (e.g. a direction) about the line through the segment.
+    pub fn reflect(&self, v: &Vec2) -> Vec2 {
+        match self.normal() {
+            Some(normal) => reflect(v, &normal),
+            None => *v,
+        }
+    }
+
+    /// Mirrors the point about the line through the segment.
+    pub fn reflect_point(&self, p: &Vec2) -> Vec2 {
+        self.a + self.reflect(&(*p - self.a))
+    }
+}
+
+/// Parameters `(t, u)` where the line `origin + dir * t` crosses the line `segment.a + segment.direction() * u`.
+fn intersect(origin: Vec2, dir: Vec2, segment: &Segment) -> Option<(f64, f64)> {
+    let other = segment.direction();
+    let denom = dir.cross(&other);
+    if denom.abs() < EPSILON {
+        return None;
+    }
+    let offset = segment.a - origin;
+    Some((offset.cross(&other) / denom, offset.cross(&dir) / denom))
+}
+
+/// Half-line from `origin` in the direction `dir` - `dir` doesn't need to be a unit vector.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Ray {
+    pub(crate) origin: Vec2,
+    pub(crate) dir: Vec2,
+}
+
+impl Ray {
+    pub fn new(origin: Vec2, dir: Vec2) -> Self {
+        Ray { origin, dir }
+    }
+
+    /// Ray from `from` through `to`, starting at `to`.
+    pub fn through(from: &Vec2, to: &Vec2) -> Self {
+        Ray::new(*to, *to - *from)
+    }
+
+    pub fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    pub fn dir(&self) -> Vec2 {
+        self.dir
+    }
+
+    /// Point at `origin + dir * t`.
+    pub fn at(&self, t: f64) -> Vec2 {
+        self.origin + self.dir * t
+    }
+
+    /// Parameter `t` and point where the ray crosses the segment.
+    pub fn intersection(&self, segment: &Segment) -> Option<(f64, Vec2)> {
+        let (t, u) = intersect(self.origin, self.dir, segment)?;
+        if t >= 0. && u >= -EPSILON && u <= 1. + EPSILON {
+            Some((t, self.at(t)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Contact of a circle sweeping along a ray - see `Polygon::sweep_circle`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Hit {
+    /// ray parameter of the contact
+    pub t: f64,
+    /// center of the circle at the contact
+    pub center: Vec2,
+    /// unit normal of the contact - points to the circle center
+    pub normal: Vec2,
+}
+
+impl Hit {
+    /// Direction after the bounce.
+    pub fn reflect(&self, dir: &Vec2) -> Vec2 {
+        reflect(dir, &self.normal)
+    }
+}
+
+/// Closed polygon - the last vertex connects to the first.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Polygon {
+    pub(crate) vertices: Vec<Vec2>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Vec2>) -> Self {
+        Polygon { vertices }
+    }
+
+    pub fn from_points(points: &[Point]) -> Self {
+        Polygon::new(points.iter().map(Vec2::from).collect())
+    }
+
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| Segment::new(self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Signed area - positive if the vertices are counter-clockwise (with the y axis pointing up).
+    pub fn signed_area(&self) -> f64 {
+        self.edges().map(|e| e.a.cross(&e.b)).sum::<f64>() / 2.
+    }
+
+    /// Point in polygon test (even-odd rule).
+    pub fn contains(&self, p: &Vec2) -> bool {
+        let mut inside = false;
+        for e in self.edges() {
+            if (e.a.y > p.y) != (e.b.y > p.y) {
+                let x = e.a.x + (p.y - e.a.y) / (e.b.y - e.a.y) * (e.b.x - e.a.x);
+                if p.x < x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
+    /// First contact of a circle inside the polygon, which moves along the ray, with a wall or corner.
+    ///
+    /// The ray origin is the circle center - `None` if the circle doesn't move towards any wall.
+    pub fn sweep_circle(&self, ray: &Ray, radius: f64) -> Option<Hit> {
+        // the walls are hit from the inside - the normals are oriented by the winding order
+        let winding = self.signed_area().signum();
+        let mut first: Option<Hit> = None;
+        let mut keep = |hit: Hit| {
+            if first.map_or(true, |first| hit.t < first.t) {
+                first = Some(hit);
+            }
+        };
+
+        for edge in self.edges() {
+            let normal = match edge.normal() {
+                Some(normal) => normal * winding,
+                None => continue,
+            };
+            // approach speed and distance to the wall
+            let speed = ray.dir.dot(&normal);
+            if speed > -EPSILON {
+                continue;
+            }
+            let dist = (ray.origin - edge.a).dot(&normal);
+            let t = ((radius - dist) / speed).max(0.);
+            let center = ray.at(t);
+            // the contact must be on the wall - otherwise the circle hits a corner
+            let along = (center - edge.a).dot(&edge.direction()) / edge.direction().dot(&edge.direction());
+            if (0. ..=1.).contains(&along) {
+                keep(Hit { t, center, normal });
+            }
+        }
+
+        for vertex in &self.vertices {
+            let offset = ray.origin - *vertex;
+            let b = offset.dot(&ray.dir);
+            if b >= 0. {
+                continue;
+            }
+            // |offset + dir * t| = radius
+            let a = ray.dir.dot(&ray.dir);
+            let c = offset.dot(&offset) - radius * radius;
+            let disc = b * b - a * c;
+            if disc < 0. {
+                continue;
+            }
+            let t = ((-b - disc.sqrt()) / a).max(0.);
+            let center = ray.at(t);
+            if let Some(normal) = (center - *vertex).normalize() {
+                keep(Hit { t, center, normal });
+            }
+        }
+
+        first
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f64, y: f64) -> Vec2 {
+        Vec2::new(x, y)
+    }
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.dist(&b) < 1e-6
+    }
+
+    fn table() -> Polygon {
+        Polygon::new(vec![v(0., 0.), v(100., 0.), v(100., 200.), v(0., 200.)])
+    }
+
+    #[test]
+    fn segment_intersection() {
+        let a = Segment::new(v(0., 0.), v(10., 10.));
+        let vertical = Segment::new(v(5., -5.), v(5., 20.));
+        assert!(close(a.intersection(&vertical).unwrap(), v(5., 5.)));
+
+        let parallel = Segment::new(v(1., 0.), v(11., 10.));
+        assert_eq!(a.intersection(&parallel), None);
+
+        let short = Segment::new(v(20., 0.), v(20., 30.));
+        assert_eq!(a.intersection(&short), None);
+
+        let ray = Ray::through(&v(0., 0.), &v(1., 1.));
+        let (t, p) = ray.intersection(&short).unwrap();
+        assert!((t - 19.).abs() < 1e-9);
+        assert!(close(p, v(20., 20.)));
+        assert_eq!(Ray::new(v(0., 0.), v(-1., -1.)).intersection(&short), None);
+    }
+
+    #[test]
+    fn point_to_segment() {
+        let s = Segment::new(v(0., 0.), v(10., 0.));
+        assert!((s.distance(&v(5., 3.)) - 3.).abs() < 1e-9);
+        assert!((s.distance(&v(13., 4.)) - 5.).abs() < 1e-9);
+        assert!(close(s.closest_point(&v(-2., 1.)), v(0., 0.)));
+    }
+
+    #[test]
+    fn reflection() {
+        let vertical = Segment::new(v(10., 0.), v(10., 100.));
+        assert!(close(vertical.reflect(&v(3., 4.)), v(-3., 4.)));
+        assert!(close(vertical.reflect_point(&v(7., 5.)), v(13., 5.)));
+
+        let diagonal = Segment::new(v(0., 0.), v(10., 10.));
+        assert!(close(diagonal.reflect_point(&v(1., 0.)), v(0., 1.)));
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let table = table();
+        assert!(table.contains(&v(50., 50.)));
+        assert!(!table.contains(&v(150., 50.)));
+        assert!(!table.contains(&v(50., -1.)));
+
+        let l_shape = Polygon::new(vec![
+            v(0., 0.),
+            v(10., 0.),
+            v(10., 5.),
+            v(5., 5.),
+            v(5., 10.),
+            v(0., 10.),
+        ]);
+        assert!(l_shape.contains(&v(2., 8.)));
+        assert!(!l_shape.contains(&v(8., 8.)));
+    }
+
+    #[test]
+    fn circle_sweep() {
+        // both winding orders
+        let mut reversed = table();
+        reversed.vertices.reverse();
+        for table in &[table(), reversed] {
+            let hit = table.sweep_circle(&Ray::new(v(50., 100.), v(1., 0.)), 5.).unwrap();
+            assert!((hit.t - 45.).abs() < 1e-9);
+            assert!(close(hit.normal, v(-1., 0.)));
+
+            // into the corner - the circle touches both walls at once
+            let hit = table.sweep_circle(&Ray::new(v(50., 50.), v(-1., -1.)), 10.).unwrap();
+            assert!(close(hit.center, v(10., 10.)));
+
+            // moving away from the wall after the bounce
+            let ray = Ray::new(v(95., 100.), v(-1., 1.));
+            let hit = table.sweep_circle(&ray, 5.).unwrap();
+            assert!(close(hit.center, v(5., 190.)));
+        }
+        assert_eq!(table().sweep_circle(&Ray::new(v(50., 50.), v(0., 0.)), 5.), None);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::*;
+use opencv::{
+    calib3d::find_homography,
+    core::{perspective_transform, Mat as OpencvMat, Point2f, Size},
+    imgproc::warp_perspective,
+    types::VectorOfPoint2f,
+};
+
+/// Perspective transformation between the camera image and the table plane.
+///
+/// Computed once from the four table corners in the image - keep the instance
+/// and recompute it only when the corners change (see `Homography::matches`).
+///
+/// With a `Calibration`, the lens distortion is removed in the same step:
+/// frames are warped with a single `remap` and the points are undistorted
+/// before they are mapped to the table.
+///
+/// ```no_run
+/// # use cv::*;
+/// let corners = [Point::new(12, 8), Point::new(950, 15), Point::new(940, 540), Point::new(5, 530)];
+/// let homography = Homography::from_corners(&corners)?;
+/// let frame = imread("frame.png")?;
+/// let table = homography.warp(&frame)?;
+/// let p = homography.to_table(&Point::new(480, 270))?;
+/// assert_eq!(homography.to_image(&p)?, Point::new(480, 270));
+/// # Ok::<(), cv::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Homography {
+    corners: [Point; 4],
+    width: i32,
+    height: i32,
+    /// image -> table
+    matrix: OpencvMat,
+    /// table -> image
+    inverse: OpencvMat,
+    calibration: Option<Calibration>,
+    /// lookup maps to undistort and warp a frame in one step
+    maps: Option<(OpencvMat, OpencvMat)>,
+}
+
+impl Homography {
+    /// Maps the corners (left-top, right-top, right-bottom, left-bottom) to a table of the given size.
+    pub fn new(corners: &[Point; 4], width: i32, height: i32) -> Result<Homography> {
+        Homography::build(corners, width, height, None)
+    }
+
+    /// Like `Homography::new` - but removes the lens distortion with the given calibration.
+    ///
+    /// The corners are in the distorted (raw) image with the given `image_size` (width, height) -
+    /// fails if the calibration was made with another resolution (see `Calibration::check_image_size`).
+    pub fn with_calibration(
+        corners: &[Point; 4],
+        width: i32,
+        height: i32,
+        calibration: Option<&Calibration>,
+        image_size: (i32, i32),
+    ) -> Result<Homography> {
+        if let Some(calibration) = calibration {
+            calibration.check_image_size(image_size.0, image_size.1)?;
+        }
+        Homography::build(corners, width, height, calibration)
+    }
+
+    fn build(corners: &[Point; 4], width: i32, height: i32, calibration: Option<&Calibration>) -> Result<Homography> {
+        if width <= 0 || height <= 0 {
+            return Err(Error::UserInput {
+                msg: format!("invalid table size: {}x{}", width, height),
+            });
+        }
+        let table = [
+            Point::new(0, 0),
+            Point::new(width, 0),
+            Point::new(width, height),
+            Point::new(0, height),
+        ];
+        let undistorted = match calibration {
+            Some(calibration) => calibration.undistort_points(corners)?,
+            None => corners.to_vec(),
+        };
+        let src = OpencvMat::from_exact_iter(undistorted.iter().map(|p| p.unpack()))?;
+        let dst = OpencvMat::from_exact_iter(table.iter().map(|p| p.unpack()))?;
+        let matrix = find_homography(&src, &dst, &mut OpencvMat::default()?, 0, 3.)?;
+        let inverse = find_homography(&dst, &src, &mut OpencvMat::default()?, 0, 3.)?;
+        let maps = match calibration {
+            Some(calibration) => Some(calibration.rectify_maps(&matrix, width, height)?),
+            None => None,
+        };
+        Ok(Homography {
+            corners: *corners,
+            width,
+            height,
+            matrix,
+            inverse,
+            calibration: calibration.cloned(),
+            maps,
+        })
+    }
+
+    /// Like `Homography::new` with the size from `Homography::size_from_corners`.
+    pub fn from_corners(corners: &[Point; 4]) -> Result<Homography> {
+        let (width, height) = Homography::size_from_corners(corners);
+        Homography::new(corners, width, height)
+    }
+
+    /// Table size which keeps the resolution of the longer opposite edges.
+    pub fn size_from_corners(corners: &[Point; 4]) -> (i32, i32) {
+        let [lt, rt, rb, lb] = corners;
+        let width = lt.dist(rt).max(lb.dist(rb));
+        let height = lt.dist(lb).max(rt.dist(rb));
+        (width.round() as i32, height.round() as i32)
+    }
+
+    /// `true` if the homography was computed from the given corners, size and calibration.
+    pub fn matches(&self, corners: &[Point; 4], width: i32, height: i32, calibration: Option<&Calibration>) -> bool {
+        self.corners == *corners
+            && self.width == width
+            && self.height == height
+            && self.calibration.as_ref() == calibration
+    }
+
+    pub fn corners(&self) -> &[Point; 4] {
+        &self.corners
+    }
+
+    /// Width of the warped table frame.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Height of the warped table frame.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Warps the frame to the table plane.
+    pub fn warp<ColorSpace>(&self, frame: &Mat<ColorSpace>) -> Result<Mat<ColorSpace>> {
+        if let Some(maps) = &self.maps {
+            return calibration::remap_frame(frame, maps);
+        }
+        let mut warped = OpencvMat::default()?;
+        warp_perspective(
+            frame.unpack(),
+            &mut warped,
+            &self.matrix,
+            Size::new(self.width, self.height),
+            opencv::imgproc::INTER_LINEAR,
+            opencv::core::BORDER_CONSTANT,
+            opencv::core::Scalar::default(),
+        )?;
+        Ok(Mat::pack(warped))
+    }
+
+    /// Maps the image point to table coordinates.
+    pub fn to_table(&self, p: &Point) -> Result<Point> {
+        Ok(self.points_to_table(&[*p])?[0])
+    }
+
+    /// Maps the table point to image coordinates.
+    pub fn to_image(&self, p: &Point) -> Result<Point> {
+        Ok(self.points_to_image(&[*p])?[0])
+    }
+
+    /// Maps the image points to table coordinates.
+    pub fn points_to_table(&self, ps: &[Point]) -> Result<Vec<Point>> {
+        match &self.calibration {
+            Some(calibration) if !ps.is_empty() => transform(&self.matrix, &calibration.undistort_points(ps)?),
+            _ => transform(&self.matrix, ps),
+        }
+    }
+
+    /// Maps the table points to image coordinates.
+    pub fn points_to_image(&self, ps: &[Point]) -> Result<Vec<Point>> {
+        let undistorted = transform(&self.inverse, ps)?;
+        match &self.calibration {
+            Some(calibration) if !ps.is_empty() => calibration.distort_points(&undistorted),
+            _ => Ok(undistorted),
+        }
+    }
+}
+
+fn transform(matrix: &OpencvMat, ps: &[Point]) -> Result<Vec<Point>> {
+    if ps.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut src = VectorOfPoint2f::with_capacity(ps.len());
+    for p in ps {
+        src.push(Point2f::new(p.x as f32, p.y as f32));
+    }
+    let mut dst = VectorOfPoint2f::new();
+    perspective_transform(&src, &mut dst, matrix)?;
+    Ok(dst
+        .iter()
+        .map(|p| Point::new(p.x.round() as i32, p.y.round() as i32))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_points_both_ways() {
+        let corners = [
+            Point::new(10, 20),
+            Point::new(210, 20),
+            Point::new(210, 120),
+            Point::new(10, 120),
+        ];
+        let homography = Homography::new(&corners, 400, 200).unwrap();
+        assert_eq!(homography.to_table(&Point::new(10, 20)).unwrap(), Point::new(0, 0));
+        assert_eq!(
+            homography.to_table(&Point::new(210, 120)).unwrap(),
+            Point::new(400, 200)
+        );
+        assert_eq!(homography.to_table(&Point::new(110, 70)).unwrap(), Point::new(200, 100));
+
+        let p = Point::new(42, 77);
+        let table = homography.to_table(&p).unwrap();
+        assert_eq!(homography.to_image(&table).unwrap(), p);
+    }
+
+    #[test]
+    fn size_from_all_edges() {
+        let corners = [
+            Point::new(0, 0),
+            Point::new(100, 0),
+            Point::new(120, 60),
+            Point::new(-20, 50),
+        ];
+        assert_eq!(Homography::size_from_corners(&corners), (140, 63));
+    }
+}
@@ -0,0 +1,133 @@
+//!
+//! Circle detection with the Hough transform - independent of the object color.
+//!
+use crate::*;
+use opencv::{imgproc::HOUGH_GRADIENT, types::VectorOfVec3f};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of the Hough circle transform - see `HoughCircles::detect`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoughCircles {
+    /// inverse ratio of the accumulator resolution to the image resolution - 1.0: same resolution
+    pub dp: f64,
+    /// minimum distance between the centers of two circles
+    pub min_dist: f64,
+    /// upper threshold of the Canny edge detector (the lower threshold is the half)
+    pub canny_threshold: f64,
+    /// accumulator threshold - smaller values detect more (false) circles
+    pub accumulator_threshold: f64,
+    /// radius range in pixels - a `max_radius` of 0 means unbounded
+    pub min_radius: i32,
+    pub max_radius: i32,
+}
+
+impl Default for HoughCircles {
+    fn default() -> Self {
+        Self {
+            dp: 1.0,
+            min_dist: 20.0,
+            canny_threshold: 100.0,
+            accumulator_threshold: 30.0,
+            min_radius: 10,
+            max_radius: 40,
+        }
+    }
+}
+
+impl HoughCircles {
+    /// Circles with a known radius (e.g. the puck) - the radius range is `radius` +- `tolerance`.
+    pub fn with_radius(radius: i32, tolerance: i32) -> Self {
+        Self {
+            min_dist: radius as f64 * 2.,
+            min_radius: (radius - tolerance).max(0),
+            max_radius: radius + tolerance,
+            ..Self::default()
+        }
+    }
+
+    /// Copy with the distances and radii scaled by the factor - e.g. from the warped table to the raw frame.
+    ///
+    /// An unbounded `max_radius` (0) stays unbounded.
+    pub fn scaled(&self, factor: f64) -> Self {
+        let scale = |r: i32| (r as f64 * factor).round() as i32;
+        Self {
+            min_dist: self.min_dist * factor,
+            min_radius: scale(self.min_radius),
+            max_radius: scale(self.max_radius),
+            ..self.clone()
+        }
+    }
+
+    /// Detects the circles in the frame - the best matches first.
+    ///
+    /// Blur the frame before (e.g. with `Filter::blur`) to suppress the noise.
+    pub fn detect(&self, frame: &Mat<Gray>) -> Result<Vec<Circle>> {
+        if self.dp <= 0. || self.min_radius < 0 || (self.max_radius > 0 && self.max_radius < self.min_radius) {
+            return Err(Error::UserInput {
+                msg: format!("invalid hough circle parameters: {:?}", self),
+            });
+        }
+
+        let mut circles = VectorOfVec3f::new();
+        opencv::imgproc::hough_circles(
+            frame.unpack(),
+            &mut circles,
+            HOUGH_GRADIENT,
+            self.dp,
+            self.min_dist,
+            self.canny_threshold,
+            self.accumulator_threshold,
+            self.min_radius,
+            self.max_radius,
+        )?;
+        Ok(circles
+            .iter()
+            .map(|c| Circle {
+                center: Point2f::new(c[0] as f64, c[1] as f64),
+                radius: c[2] as f64,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_drawn_circle() {
+        let mut frame = Mat::<BGR>::new(200, 300, RGB::black()).unwrap();
+        frame.draw_circle(&Point::new(120, 80), 25, RGB::white(), -1);
+        let mut gray: Mat<Gray> = frame.convert_color();
+        gray.blur(3);
+
+        let circles = HoughCircles::with_radius(25, 5).detect(&gray).unwrap();
+        let circle = circles.first().expect("circle");
+        assert!(circle.center.dist(&Point2f::new(120., 80.)) < 3.);
+        assert!((circle.radius - 25.).abs() < 3.);
+    }
+
+    #[test]
+    fn scaled() {
+        let params = HoughCircles::with_radius(20, 5).scaled(0.5);
+        assert_eq!(params.min_dist, 20.);
+        assert_eq!((params.min_radius, params.max_radius), (8, 13));
+
+        let unbounded = HoughCircles {
+            max_radius: 0,
+            ..HoughCircles::default()
+        };
+        assert_eq!(unbounded.scaled(2.).max_radius, 0);
+    }
+
+    #[test]
+    fn reject_invalid_parameters() {
+        let gray = Mat::<Gray>::new(10, 10, RGB::black()).unwrap();
+        let params = HoughCircles {
+            min_radius: 20,
+            max_radius: 10,
+            ..HoughCircles::default()
+        };
+        assert!(params.detect(&gray).is_err());
+    }
+}
@@ -1,5 +1,11 @@
 use crate::*;
-use std::path::PathBuf;
+use log::{debug, warn};
+use std::{
+    cmp::Ordering,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 pub fn imread<P>(p: P) -> Result<Mat<BGR>>
 where
@@ -21,3 +27,308 @@ where
         imread_unchanged,
     )?))
 }
+
+/// Writes the frame to an image file - the format is taken from the file extension.
+pub fn imwrite<P, ColorSpace>(p: P, frame: &Mat<ColorSpace>) -> Result<()>
+where
+    P: Into<PathBuf>,
+{
+    let path = p.into();
+    if !opencv::imgcodecs::imwrite(
+        &path.to_string_lossy(),
+        frame.unpack(),
+        &opencv::types::VectorOfint::new(),
+    )? {
+        return Err(Error::VideoIO {
+            source: opencv::Error::new(0, format!("unable to write file: {}", path.to_string_lossy())),
+        });
+    }
+    Ok(())
+}
+
+/// Name of the optional timestamps sidecar file in a image sequence directory.
+pub const TIMESTAMPS_FILE: &str = "timestamps.txt";
+
+/// Sequence of numbered image files (PNG / JPEG) in a directory.
+///
+/// The files are ordered in natural sort order, so `frame-2.png`
+/// comes before `frame-10.png`.
+///
+/// If the directory contains a `timestamps.txt` file, each line
+/// assigns a capture timestamp (in milliseconds) to a file:
+///
+/// ```text
+/// frame-0001.png 0
+/// frame-0002.png 33
+/// ```
+pub struct ImageSequence {
+    frames: Vec<(PathBuf, Option<Duration>)>,
+    pos: usize,
+    current: Option<usize>,
+    looping: bool,
+}
+
+impl ImageSequence {
+    /// Opens the image sequence in the given directory.
+    pub fn open(dir: &Path) -> Result<ImageSequence> {
+        if !dir.is_dir() {
+            return Err(Error::VideoIO {
+                source: opencv::Error::new(0, format!("not a directory: {}", dir.to_string_lossy())),
+            });
+        }
+
+        let mut files = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| is_image(p))
+            .collect::<Vec<_>>();
+        files.sort_by(|a, b| natural_cmp(&file_name(a), &file_name(b)));
+
+        if files.is_empty() {
+            return Err(Error::VideoIO {
+                source: opencv::Error::new(0, format!("no images found in: {}", dir.to_string_lossy())),
+            });
+        }
+
+        let timestamps = read_timestamps(&dir.join(TIMESTAMPS_FILE))?;
+        let frames = files
+            .into_iter()
+            .map(|p| {
+                let ts = timestamps
+                    .iter()
+                    .find(|(name, _)| *name == file_name(&p))
+                    .map(|(_, ts)| *ts);
+                (p, ts)
+            })
+            .collect::<Vec<_>>();
+
+        debug!(
+            "image sequence with {} frames in {} - timestamps: {}",
+            frames.len(),
+            dir.display(),
+            !timestamps.is_empty()
+        );
+        Ok(ImageSequence {
+            frames,
+            pos: 0,
+            current: None,
+            looping: false,
+        })
+    }
+
+    /// Start from the beginning when the last image was read.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Number of images in the sequence.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Index of the image which is returned by the next call of `grab`.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Seeks to the image with the given index.
+    pub fn seek(&mut self, n: usize) {
+        self.pos = n.min(self.frames.len() - 1);
+    }
+
+    /// Seeks to the first image with a timestamp at or after the given timestamp.
+    ///
+    /// Returns `false` if the images have no timestamps.
+    pub fn seek_timestamp(&mut self, ts: Duration) -> bool {
+        if self.frames.iter().all(|(_, t)| t.is_none()) {
+            return false;
+        }
+        let n = self
+            .frames
+            .iter()
+            .position(|(_, t)| t.map(|t| t >= ts).unwrap_or(false))
+            .unwrap_or(self.frames.len() - 1);
+        self.seek(n);
+        true
+    }
+
+    /// Timestamp of the image which was returned by the last call of `grab`.
+    ///
+    /// `None` if there is no timestamp sidecar file or the image has no entry.
+    pub fn timestamp(&self) -> Option<Duration> {
+        self.current.and_then(|n| self.frames[n].1)
+    }
+
+    /// Reads the image at the current position - fails after the last image (see `ImageSequence::looping`).
+    pub fn grab(&mut self) -> Result<Mat<BGR>> {
+        let (path, _) = self.frames.get(self.pos).ok_or_else(|| Error::VideoIO {
+            source: opencv::Error::new(0, format!("end of the image sequence reached: {}", self.pos)),
+        })?;
+        let mat = imread(path)?;
+        self.current = Some(self.pos);
+        self.pos += 1;
+        Ok(mat)
+    }
+}
+
+impl Iterator for ImageSequence {
+    type Item = Mat<BGR>;
+    fn next(&mut self) -> Option<Self::Item> {
+        // stop after a full pass without a readable image - otherwise a looping sequence never ends
+        let mut unreadable = 0;
+        while unreadable < self.frames.len() {
+            if self.pos >= self.frames.len() {
+                if !self.looping {
+                    return None;
+                }
+                debug!("image sequence end reached - start from the beginning");
+                self.pos = 0;
+            }
+
+            match self.grab() {
+                Ok(mat) if !mat.is_empty().unwrap_or(true) => return Some(mat),
+                Ok(_) => warn!("skip unreadable image: {}", self.frames[self.pos - 1].0.display()),
+                Err(err) => {
+                    warn!("skip image: {}", err);
+                    self.pos += 1;
+                }
+            }
+            unreadable += 1;
+        }
+        warn!("no readable image in the sequence");
+        None
+    }
+}
+
+fn is_image(p: &Path) -> bool {
+    p.is_file()
+        && p.extension()
+            .map(|ext| {
+                let ext = ext.to_string_lossy().to_lowercase();
+                ext == "png" || ext == "jpg" || ext == "jpeg"
+            })
+            .unwrap_or(false)
+}
+
+fn file_name(p: &Path) -> String {
+    p.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn read_timestamps(path: &Path) -> Result<Vec<(String, Duration)>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut cols = line.split_whitespace();
+            match (cols.next(), cols.next().map(str::parse::<u64>)) {
+                (Some(name), Some(Ok(millis))) => Ok((name.to_string(), Duration::from_millis(millis))),
+                _ => Err(Error::VideoIO {
+                    source: opencv::Error::new(
+                        0,
+                        format!(
+                            "invalid line in {}: '{}' - expected: <file-name> <millis>",
+                            path.display(),
+                            line
+                        ),
+                    ),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Compares strings in natural order - numbers are compared by their value.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    #[derive(PartialEq, Eq, PartialOrd, Ord)]
+    enum Chunk<'a> {
+        Number(u64),
+        Text(&'a str),
+    }
+
+    fn chunks(s: &str) -> Vec<Chunk> {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        let bytes = s.as_bytes();
+        while start < bytes.len() {
+            let is_digit = bytes[start].is_ascii_digit();
+            let end = bytes[start..]
+                .iter()
+                .position(|b| b.is_ascii_digit() != is_digit)
+                .map(|n| start + n)
+                .unwrap_or_else(|| bytes.len());
+            let chunk = &s[start..end];
+            chunks.push(if is_digit {
+                Chunk::Number(chunk.parse().unwrap_or(u64::max_value()))
+            } else {
+                Chunk::Text(chunk)
+            });
+            start = end;
+        }
+        chunks
+    }
+
+    chunks(a).cmp(&chunks(b)).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_sort() {
+        let mut names = vec!["frame-10.png", "frame-2.png", "frame-1.png", "a.png", "frame-02.png"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            vec!["a.png", "frame-1.png", "frame-02.png", "frame-2.png", "frame-10.png"]
+        );
+    }
+
+    #[test]
+    fn looping_sequence_without_readable_images_ends() {
+        let dir = testing::TempDir::new("unreadable");
+        fs::write(dir.path().join("frame-1.png"), b"no image").unwrap();
+        fs::write(dir.path().join("frame-2.png"), b"no image").unwrap();
+
+        let mut seq = ImageSequence::open(dir.path()).unwrap().looping(true);
+        assert!(seq.next().is_none());
+    }
+
+    #[test]
+    fn seek() {
+        let dir = testing::image_dir("seek", 4);
+        let value = |mat: Mat<BGR>| testing::image_index(&mat);
+
+        let mut seq = ImageSequence::open(dir.path()).unwrap();
+        assert!(!seq.seek_timestamp(Duration::from_millis(40)));
+        seq.seek(2);
+        assert_eq!(seq.position(), 2);
+        assert_eq!(value(seq.next().unwrap()), 2);
+        seq.seek(10);
+        assert_eq!(value(seq.next().unwrap()), 3);
+        assert!(seq.grab().is_err());
+
+        fs::write(
+            dir.path().join(TIMESTAMPS_FILE),
+            "frame-0.png 0\nframe-1.png 33\nframe-2.png 66\nframe-3.png 100\n",
+        )
+        .unwrap();
+        let mut seq = ImageSequence::open(dir.path()).unwrap();
+        assert!(seq.seek_timestamp(Duration::from_millis(40)));
+        assert_eq!(value(seq.next().unwrap()), 2);
+        assert_eq!(seq.timestamp(), Some(Duration::from_millis(66)));
+        assert!(seq.seek_timestamp(Duration::from_secs(5)));
+        assert_eq!(seq.position(), 3);
+    }
+}
@@ -1,14 +1,27 @@
 use opencv::prelude::Vector;
 
+pub mod background;
+pub mod calibration;
 //pub mod cam;
 pub mod colors;
 pub mod contour;
 pub mod error;
+pub mod geometry;
 pub mod gui;
+pub mod homography;
+pub mod hough;
 pub mod imageio;
+pub mod markers;
 pub mod mat;
+pub mod mjpeg;
 pub mod point;
 pub mod rect;
+pub mod table;
+pub mod template;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod threaded_capture;
+pub mod vec2;
 pub mod videoio;
 
 pub mod prelude {
@@ -16,21 +29,36 @@ pub mod prelude {
     pub use crate::mat::convert_color::ConvertColor;
     pub use crate::mat::draw::Draw;
     pub use crate::mat::filter::Filter;
+    pub use crate::mat::pixel::Pixel;
+    pub use crate::mat::FindCenter;
     pub use crate::mat::FindContours;
     pub use crate::mat::InRange;
 }
 
+pub use crate::background::{BackgroundModel, BackgroundSettings, BackgroundSubtractor};
+pub use crate::calibration::{Calibration, ChessboardCalibrator, ChessboardView};
 pub use crate::colors::*;
 pub use crate::contour::*;
 pub use crate::error::*;
+pub use crate::geometry::{reflect, Hit, Polygon, Ray, Segment};
 pub use crate::gui::mouse_events::*;
 pub use crate::gui::GUI;
-pub use crate::imageio::imread;
+pub use crate::homography::Homography;
+pub use crate::hough::HoughCircles;
+pub use crate::imageio::{imread, imwrite, ImageSequence};
+pub use crate::markers::{Marker, MarkerDetector, MarkerDictionary};
 pub use crate::mat::Mat;
+pub use crate::mjpeg::MjpegServer;
 pub use crate::point::*;
 pub use crate::prelude::*;
 pub use crate::rect::*;
-pub use crate::videoio::{VideoCapture, VideoWriter};
+pub use crate::table::{check_quad, detect_table, order_corners, TableQuad};
+pub use crate::template::{TemplateMatch, TemplateMatcher};
+pub use crate::threaded_capture::{ThreadedCapture, TimestampedFrame};
+pub use crate::vec2::*;
+pub use crate::videoio::{
+    CaptureProperty, CaptureSettings, FourCC, RetryPolicy, StopSignal, VideoCapture, VideoWriter,
+};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -0,0 +1,187 @@
+//!
+//! Fiducial markers (ArUco / AprilTag) - needs the `aruco` feature (OpenCV contrib).
+//!
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+/// Predefined marker dictionaries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerDictionary {
+    Aruco4x4_50,
+    Aruco5x5_100,
+    Aruco6x6_250,
+    AprilTag16h5,
+    AprilTag36h11,
+}
+
+impl Default for MarkerDictionary {
+    fn default() -> Self {
+        MarkerDictionary::Aruco4x4_50
+    }
+}
+
+/// Detected marker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub id: i32,
+    /// corners of the marker - clockwise, starting with the top left corner of the marker image
+    pub corners: [Point; 4],
+}
+
+impl Marker {
+    pub fn center(&self) -> Point {
+        let (x, y) = self.corners.iter().fold((0, 0), |(x, y), p| (x + p.x, y + p.y));
+        Point::new(x / 4, y / 4)
+    }
+}
+
+/// Detects the markers of a dictionary.
+#[cfg(feature = "aruco")]
+pub struct MarkerDetector {
+    dictionary: opencv::types::PtrOfDictionary,
+    parameters: opencv::types::PtrOfDetectorParameters,
+}
+
+#[cfg(feature = "aruco")]
+impl MarkerDetector {
+    pub fn new(dictionary: MarkerDictionary) -> Result<MarkerDetector> {
+        use opencv::aruco::{get_predefined_dictionary, DetectorParameters, PREDEFINED_DICTIONARY_NAME::*};
+
+        let name = match dictionary {
+            MarkerDictionary::Aruco4x4_50 => DICT_4X4_50,
+            MarkerDictionary::Aruco5x5_100 => DICT_5X5_100,
+            MarkerDictionary::Aruco6x6_250 => DICT_6X6_250,
+            MarkerDictionary::AprilTag16h5 => DICT_APRILTAG_16h5,
+            MarkerDictionary::AprilTag36h11 => DICT_APRILTAG_36h11,
+        };
+        Ok(MarkerDetector {
+            dictionary: get_predefined_dictionary(name)?,
+            parameters: DetectorParameters::create()?,
+        })
+    }
+
+    /// Detects all markers in the frame.
+    pub fn detect(&self, frame: &Mat<BGR>) -> Result<Vec<Marker>> {
+        use opencv::{
+            core::Mat as OpencvMat,
+            types::{VectorOfVectorOfPoint2f, VectorOfint},
+        };
+
+        let mut corners = VectorOfVectorOfPoint2f::new();
+        let mut ids = VectorOfint::new();
+        opencv::aruco::detect_markers(
+            frame.unpack(),
+            &self.dictionary,
+            &mut corners,
+            &mut ids,
+            &self.parameters,
+            &mut VectorOfVectorOfPoint2f::new(),
+            &OpencvMat::default()?,
+            &OpencvMat::default()?,
+        )?;
+
+        let mut markers = Vec::with_capacity(ids.len());
+        for (id, corners) in ids.iter().zip(corners.iter()) {
+            let mut marker = Marker {
+                id,
+                corners: [Point::default(); 4],
+            };
+            for (i, p) in corners.iter().take(4).enumerate() {
+                marker.corners[i] = Point::new(p.x.round() as i32, p.y.round() as i32);
+            }
+            markers.push(marker);
+        }
+        Ok(markers)
+    }
+}
+
+/// Placeholder without the `aruco` feature - `MarkerDetector::new` always fails.
+#[cfg(not(feature = "aruco"))]
+pub struct MarkerDetector(());
+
+#[cfg(not(feature = "aruco"))]
+impl MarkerDetector {
+    pub fn new(_dictionary: MarkerDictionary) -> Result<MarkerDetector> {
+        Err(Error::UserInput {
+            msg: "marker detection needs the `aruco` feature".into(),
+        })
+    }
+
+    pub fn detect(&self, _frame: &Mat<BGR>) -> Result<Vec<Marker>> {
+        Ok(Vec::new())
+    }
+}
+
+impl MarkerDetector {
+    /// Table corners from the markers with the given ids (left-top, right-top, right-bottom, left-bottom).
+    ///
+    /// The markers are expected inside the playing field, flush with its corners - so
+    /// each table corner is the marker corner which points outwards (away from the
+    /// center between the markers). `None` unless all four markers were found.
+    pub fn find_corners(&self, frame: &Mat<BGR>, ids: &[i32; 4]) -> Result<Option<[Point; 4]>> {
+        let markers = self.detect(frame)?;
+        Ok(corners_by_id(&markers, ids))
+    }
+}
+
+fn corners_by_id(markers: &[Marker], ids: &[i32; 4]) -> Option<[Point; 4]> {
+    let mut found = Vec::with_capacity(4);
+    for id in ids {
+        found.push(markers.iter().find(|m| m.id == *id)?);
+    }
+    let (x, y) = found
+        .iter()
+        .map(|m| m.center())
+        .fold((0, 0), |(x, y), p| (x + p.x, y + p.y));
+    let center = Point::new(x / 4, y / 4);
+
+    let mut corners = [Point::default(); 4];
+    for (corner, marker) in corners.iter_mut().zip(found) {
+        *corner = *marker
+            .corners
+            .iter()
+            .max_by_key(|p| p.dist(&center).round() as i64)
+            .expect("marker corners");
+    }
+    Some(corners)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(id: i32, x: i32, y: i32) -> Marker {
+        Marker {
+            id,
+            corners: [
+                Point::new(x - 5, y - 5),
+                Point::new(x + 5, y - 5),
+                Point::new(x + 5, y + 5),
+                Point::new(x - 5, y + 5),
+            ],
+        }
+    }
+
+    #[test]
+    fn table_corners_from_marker_ids() {
+        let markers = vec![
+            marker(7, 500, 400),
+            marker(3, 20, 10),
+            marker(12, 300, 300),
+            marker(4, 510, 15),
+            marker(5, 10, 390),
+        ];
+        // the outer corners of the markers
+        let corners = corners_by_id(&markers, &[3, 4, 7, 5]).unwrap();
+        assert_eq!(
+            corners,
+            [
+                Point::new(15, 5),
+                Point::new(515, 10),
+                Point::new(505, 405),
+                Point::new(5, 395)
+            ]
+        );
+        assert_eq!(corners_by_id(&markers, &[3, 4, 7, 6]), None);
+    }
+}
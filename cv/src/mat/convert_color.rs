@@ -26,8 +26,58 @@ impl ConvertColor<BGR> for Mat<Gray> {
     }
 }
 
+impl ConvertColor<Gray> for Mat<BGR> {
+    fn convert_color(&self) -> Mat<Gray> {
+        convert(self, COLOR_BGR2GRAY)
+    }
+}
+
+impl ConvertColor<Lab> for Mat<BGR> {
+    fn convert_color(&self) -> Mat<Lab> {
+        convert(self, COLOR_BGR2Lab)
+    }
+}
+
+impl ConvertColor<BGR> for Mat<Lab> {
+    fn convert_color(&self) -> Mat<BGR> {
+        convert(self, COLOR_Lab2BGR)
+    }
+}
+
+impl ConvertColor<YCrCb> for Mat<BGR> {
+    fn convert_color(&self) -> Mat<YCrCb> {
+        convert(self, COLOR_BGR2YCrCb)
+    }
+}
+
+impl ConvertColor<BGR> for Mat<YCrCb> {
+    fn convert_color(&self) -> Mat<BGR> {
+        convert(self, COLOR_YCrCb2BGR)
+    }
+}
+
 fn convert<From, To>(mat: &Mat<From>, code: ColorConversionCodes) -> Mat<To> {
     let mut to = OpencvMat::default().expect("Mat::default()");
     opencv::imgproc::cvt_color(&mat.inner, &mut to, code as i32, 0).expect("cvt_color");
     Mat::pack(to)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_bgr() {
+        let bgr = Mat::<BGR>::new(2, 2, BGR::new(10, 20, 30)).unwrap();
+        let p = Point::new(1, 1);
+
+        let gray: Mat<Gray> = bgr.convert_color();
+        assert_eq!(gray.at(&p).unwrap(), 22);
+
+        let white = Mat::<BGR>::new(2, 2, RGB::white()).unwrap();
+        let lab: Mat<Lab> = white.convert_color();
+        assert_eq!(lab.at(&p).unwrap(), Lab::new(255, 128, 128));
+        let ycrcb: Mat<YCrCb> = white.convert_color();
+        assert_eq!(ycrcb.at(&p).unwrap(), YCrCb::new(255, 128, 128));
+    }
+}
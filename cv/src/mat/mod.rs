@@ -1,11 +1,12 @@
 use super::*;
-use log::debug;
 use opencv::{core::Mat as OpencvMat, core::Point as OpencvPoint, prelude::MatTrait};
+use pixel::Pixel;
 use std::marker::PhantomData;
 
 pub mod convert_color;
 pub mod draw;
 pub mod filter;
+pub mod pixel;
 
 #[derive(Debug)]
 pub struct Mat<ColorSpace> {
@@ -16,7 +17,13 @@ pub struct Mat<ColorSpace> {
 }
 
 impl<ColorSpace> Mat<ColorSpace> {
-    pub fn new<C: ToOpencvScalar>(rows: i32, cols: i32, cv_type: &CVType, background: C) -> Result<Mat<ColorSpace>> {
+    /// Frame with an explicit OpenCV type - prefer `Mat::new`, which derives the type from the color space.
+    pub fn with_type<C: ToOpencvScalar>(
+        rows: i32,
+        cols: i32,
+        cv_type: &CVType,
+        background: C,
+    ) -> Result<Mat<ColorSpace>> {
         Ok(Mat::pack(OpencvMat::new_rows_cols_with_default(
             rows,
             cols,
@@ -44,35 +51,70 @@ impl<ColorSpace> Mat<ColorSpace> {
         Ok(Mat::pack(opencv::core::Mat::roi(&self.inner, roi.unpack())?))
     }
 
-    pub fn lens(&self, points: &[Point; 4]) -> Result<Mat<ColorSpace>> {
-        use opencv::{calib3d::find_homography, imgproc::warp_perspective, types::VectorOfPoint};
-
-        let w = points[1].x - points[0].x;
-        let h = points[2].y - points[1].y;
-        let mut dst_corners = VectorOfPoint::with_capacity(4);
-        dst_corners.push(OpencvPoint::new(0, 0));
-        dst_corners.push(OpencvPoint::new(w, 0));
-        dst_corners.push(OpencvPoint::new(w, h));
-        dst_corners.push(OpencvPoint::new(0, h));
-
-        let roi_corners_mat = OpencvMat::from_exact_iter(points.iter().map(|p| p.unpack()))?;
-        let dst_corners_mat = OpencvMat::from_exact_iter(dst_corners.iter())?;
-
-        let hom = find_homography(&roi_corners_mat, &dst_corners_mat, &mut OpencvMat::default()?, 0, 3.)?;
-        let mut warped = OpencvMat::default()?;
-        let size = opencv::core::Size::new(w, h);
-        // https://docs.rs/opencv/0.30.1/opencv/imgproc/fn.warp_perspective.html
-        warp_perspective(
+    /// Scales the frame to the given size - the aspect ratio isn't preserved.
+    pub fn resize(&self, width: i32, height: i32) -> Result<Mat<ColorSpace>> {
+        let mut resized = OpencvMat::default()?;
+        opencv::imgproc::resize(
             &self.inner,
-            &mut warped,
-            &hom,
-            size,
-            opencv::imgproc::INTER_LINEAR,
-            opencv::core::BORDER_CONSTANT,
-            opencv::core::Scalar::default(),
+            &mut resized,
+            opencv::core::Size::new(width, height),
+            0.,
+            0.,
+            opencv::imgproc::INTER_AREA,
+        )?;
+        Ok(Mat::pack(resized))
+    }
+
+    /// Warps the area between the points (left-top, right-top, right-bottom, left-bottom) to a rectangle.
+    ///
+    /// Computes the homography on each call - use `Homography` to reuse it between frames.
+    pub fn lens(&self, points: &[Point; 4]) -> Result<Mat<ColorSpace>> {
+        Homography::from_corners(points)?.warp(self)
+    }
+
+    /// Keeps the area inside of the (convex) polygon - the rest is black.
+    pub fn mask_polygon(&self, points: &[Point]) -> Result<Mat<ColorSpace>> {
+        use opencv::{imgproc::fill_convex_poly, types::VectorOfPoint};
+
+        let mut mask = OpencvMat::new_rows_cols_with_default(
+            self.n_rows,
+            self.n_cols,
+            CVType::CV8UC1.unpack(),
+            opencv::core::Scalar::all(0.),
+        )?;
+        let mut polygon = VectorOfPoint::with_capacity(points.len());
+        for p in points {
+            polygon.push(p.unpack());
+        }
+        fill_convex_poly(
+            &mut mask,
+            &polygon,
+            opencv::core::Scalar::all(255.),
+            opencv::imgproc::LINE_8,
+            0,
         )?;
 
-        Ok(Mat::pack(warped))
+        // the newly allocated destination is initialized with zeros
+        let mut masked = OpencvMat::default()?;
+        self.inner.copy_to_masked(&mut masked, &mask)?;
+        Ok(Mat::pack(masked))
+    }
+
+    /// Places the other frame right of this frame - both need the same height.
+    pub fn hconcat(&self, other: &Mat<ColorSpace>) -> Result<Mat<ColorSpace>> {
+        let mut frames = opencv::types::VectorOfMat::new();
+        frames.push(self.inner.clone()?);
+        frames.push(other.inner.clone()?);
+        let mut combined = OpencvMat::default()?;
+        opencv::core::hconcat(&frames, &mut combined)?;
+        Ok(Mat::pack(combined))
+    }
+
+    /// Pixels which are set in both frames - e.g. to combine two masks.
+    pub fn bitwise_and(&self, other: &Mat<ColorSpace>) -> Result<Mat<ColorSpace>> {
+        let mut combined = OpencvMat::default()?;
+        opencv::core::bitwise_and(&self.inner, &other.inner, &mut combined, &OpencvMat::default()?)?;
+        Ok(Mat::pack(combined))
     }
 
     pub fn n_rows(&self) -> i32 {
@@ -87,38 +129,48 @@ impl<ColorSpace> Mat<ColorSpace> {
         Ok(self.inner.empty()?)
     }
 
-    pub fn at(&self, p: &Point) -> Result<HSV> {
-        let vec3b = self.inner.at_2d::<opencv::core::Vec3b>(p.y, p.x)?;
-        let hsv = HSV::from(RGB::from(*vec3b));
-        debug!("color at point: {} is: {}", p, hsv);
-        Ok(hsv)
+    pub fn copy_to(&self, target: &mut Mat<ColorSpace>) -> Result<()> {
+        self.unpack().copy_to(&mut target.inner)?;
+        Ok(())
+    }
+}
+
+impl<ColorSpace: Pixel> Mat<ColorSpace> {
+    /// Frame filled with the background color - the OpenCV type is derived from the color space.
+    pub fn new<C: ToOpencvScalar>(rows: i32, cols: i32, background: C) -> Result<Mat<ColorSpace>> {
+        Mat::with_type(rows, cols, &ColorSpace::CV_TYPE, background)
     }
 
-    pub fn at_avg(&self, p: &Point, size: u8) -> Result<HSV> {
-        let size = size as i32;
-        let offset = size / 2;
-        let (x, y) = if p.x - offset > 0 && p.y - offset > 0 {
-            (p.x - offset, p.y - offset)
-        } else {
-            (p.x, p.y)
-        };
+    /// Pixel at the point - e.g. `BGR` for a `Mat<BGR>`, `u8` for a `Mat<Gray>`.
+    pub fn at(&self, p: &Point) -> Result<ColorSpace::Value> {
+        if p.x < 0 || p.y < 0 || p.x >= self.n_cols || p.y >= self.n_rows {
+            return Err(Error::UserInput {
+                msg: format!("point {} outside of the frame ({}x{})", p, self.n_cols, self.n_rows),
+            });
+        }
+        ColorSpace::read(&self.inner, p)
+    }
 
-        let (mut h, mut s, mut v) = (0, 0, 0);
-        for x in x..(x + size) {
-            for y in y..(y + size) {
-                let hsv = self.at(&Point::new(x, y))?;
-                h += hsv.h as i32;
-                s += hsv.s as i32;
-                v += hsv.v as i32;
+    /// Pixels in the rect - the rect is clipped at the frame borders.
+    pub fn region(&self, rect: &Rect) -> Result<Vec<ColorSpace::Value>> {
+        let x_range = rect.x.max(0)..(rect.x + rect.width).min(self.n_cols);
+        let y_range = rect.y.max(0)..(rect.y + rect.height).min(self.n_rows);
+        let mut pixels = Vec::with_capacity(x_range.len() * y_range.len());
+        for y in y_range {
+            for x in x_range.clone() {
+                pixels.push(ColorSpace::read(&self.inner, &Point::new(x, y))?);
             }
         }
-        let n = size * size;
-        HSV::new((h / n) as u8, (s / n) as u8, (v / n) as u8)
+        Ok(pixels)
     }
 
-    pub fn copy_to(&self, target: &mut Mat<ColorSpace>) -> Result<()> {
-        self.unpack().copy_to(&mut target.inner)?;
-        Ok(())
+    /// Average of the `size` x `size` pixels around the point - clipped at the frame borders.
+    pub fn at_avg(&self, p: &Point, size: u8) -> Result<ColorSpace::Value> {
+        let size = size.max(1) as i32;
+        let pixels = self.region(&Rect::center(p, size, size))?;
+        ColorSpace::average(&pixels).ok_or_else(|| Error::UserInput {
+            msg: format!("point {} outside of the frame ({}x{})", p, self.n_cols, self.n_rows),
+        })
     }
 }
 
@@ -133,39 +185,52 @@ impl<ColorSpace> Clone for Mat<ColorSpace> {
     }
 }
 
-// pub trait FindCenter {
-//     fn find_center(&self) -> Option<Point>;
-// }
-
-// impl FindCenter for Mat<Gray> where {
-//     fn find_center(&self) -> Option<Point> {
-//         let moments = moments(&self.inner, true);
-//         if moments.m00 > 0.0 {
-//             let center = Point {
-//                 x: (moments.m10 / moments.m00) as i32,
-//                 y: (moments.m01 / moments.m00) as i32,
-//             };
-//             Some(center)
-//         } else {
-//             None
-//         }
-//     }
-// }
+pub trait FindCenter {
+    /// Sub-pixel center of mass of the non-zero pixels - `None` for an empty mask.
+    fn find_center(&self) -> Option<Point2f>;
+}
+
+impl FindCenter for Mat<Gray> {
+    fn find_center(&self) -> Option<Point2f> {
+        let binary_image = true;
+        let moments = opencv::imgproc::moments(&self.inner, binary_image).expect("moments");
+        contour::centroid(&moments)
+    }
+}
 
 pub trait InRange {
     fn in_range(&self, range: &HSVRange) -> Mat<Gray>;
 }
 
 impl InRange for Mat<HSV> {
+    /// A hue range which wraps around 0 (see `HSVRange`) combines the masks of both sub-ranges.
     fn in_range(&self, range: &HSVRange) -> Mat<Gray> {
-        let mut masked = OpencvMat::default().unwrap();
-        opencv::core::in_range(
-            &self.inner,
-            &range.min().to_opencv_scalar(),
-            &range.max().to_opencv_scalar(),
-            &mut masked,
-        )
-        .expect("in_range error");
+        let mask = |range: &HSVRange| {
+            let mut masked = OpencvMat::default().unwrap();
+            opencv::core::in_range(
+                &self.inner,
+                &range.min().to_opencv_scalar(),
+                &range.max().to_opencv_scalar(),
+                &mut masked,
+            )
+            .expect("in_range error");
+            masked
+        };
+
+        let masked = match range.split() {
+            (range, None) => mask(&range),
+            (upper, Some(lower)) => {
+                let mut combined = OpencvMat::default().unwrap();
+                opencv::core::bitwise_or(
+                    &mask(&upper),
+                    &mask(&lower),
+                    &mut combined,
+                    &OpencvMat::default().unwrap(),
+                )
+                .expect("bitwise_or error");
+                combined
+            }
+        };
         Mat::pack(masked)
     }
 }
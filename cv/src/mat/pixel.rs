@@ -0,0 +1,151 @@
+use crate::*;
+use opencv::{
+    core::{Mat as OpencvMat, Vec3b},
+    prelude::MatTrait,
+};
+use std::f64::consts::PI;
+
+/// Pixel type and typed pixel access for a color space - see `Mat::new` and `Mat::at`.
+pub trait Pixel {
+    type Value: Copy;
+
+    /// OpenCV type of a frame in this color space.
+    const CV_TYPE: CVType;
+
+    /// Reads the pixel - the point must be inside of the frame.
+    fn read(mat: &OpencvMat, p: &Point) -> Result<Self::Value>;
+
+    /// Average of the values - `None` without values.
+    fn average(values: &[Self::Value]) -> Option<Self::Value>;
+}
+
+impl Pixel for BGR {
+    type Value = BGR;
+    const CV_TYPE: CVType = CVType::CV8UC3;
+
+    fn read(mat: &OpencvMat, p: &Point) -> Result<BGR> {
+        Ok(BGR::from(*mat.at_2d::<Vec3b>(p.y, p.x)?))
+    }
+
+    fn average(values: &[BGR]) -> Option<BGR> {
+        let [b, g, r] = mean(values.iter().map(|c| [c.b, c.g, c.r]))?;
+        Some(BGR::new(b, g, r))
+    }
+}
+
+impl Pixel for HSV {
+    type Value = HSV;
+    const CV_TYPE: CVType = CVType::CV8UC3;
+
+    fn read(mat: &OpencvMat, p: &Point) -> Result<HSV> {
+        let v = mat.at_2d::<Vec3b>(p.y, p.x)?;
+        Ok(HSV::unsafe_new(v[0], v[1], v[2]))
+    }
+
+    /// The hue is averaged on the hue circle - the average of `h: 179` and `h: 1` is `h: 0`.
+    fn average(values: &[HSV]) -> Option<HSV> {
+        let [_, s, v] = mean(values.iter().map(|c| [c.h, c.s, c.v]))?;
+        let (sin, cos) = values.iter().fold((0., 0.), |(sin, cos), c| {
+            let angle = c.h as f64 * PI / 90.;
+            (sin + angle.sin(), cos + angle.cos())
+        });
+        let h = (sin.atan2(cos).rem_euclid(2. * PI) * 90. / PI).round() as u32 % 180;
+        Some(HSV::unsafe_new(h as u8, s, v))
+    }
+}
+
+impl Pixel for Lab {
+    type Value = Lab;
+    const CV_TYPE: CVType = CVType::CV8UC3;
+
+    fn read(mat: &OpencvMat, p: &Point) -> Result<Lab> {
+        let v = mat.at_2d::<Vec3b>(p.y, p.x)?;
+        Ok(Lab::new(v[0], v[1], v[2]))
+    }
+
+    fn average(values: &[Lab]) -> Option<Lab> {
+        let [l, a, b] = mean(values.iter().map(|c| [c.l, c.a, c.b]))?;
+        Some(Lab::new(l, a, b))
+    }
+}
+
+impl Pixel for YCrCb {
+    type Value = YCrCb;
+    const CV_TYPE: CVType = CVType::CV8UC3;
+
+    fn read(mat: &OpencvMat, p: &Point) -> Result<YCrCb> {
+        let v = mat.at_2d::<Vec3b>(p.y, p.x)?;
+        Ok(YCrCb::new(v[0], v[1], v[2]))
+    }
+
+    fn average(values: &[YCrCb]) -> Option<YCrCb> {
+        let [y, cr, cb] = mean(values.iter().map(|c| [c.y, c.cr, c.cb]))?;
+        Some(YCrCb::new(y, cr, cb))
+    }
+}
+
+impl Pixel for Gray {
+    type Value = u8;
+    const CV_TYPE: CVType = CVType::CV8UC1;
+
+    fn read(mat: &OpencvMat, p: &Point) -> Result<u8> {
+        Ok(*mat.at_2d::<u8>(p.y, p.x)?)
+    }
+
+    fn average(values: &[u8]) -> Option<u8> {
+        let [v, _, _] = mean(values.iter().map(|v| [*v, 0, 0]))?;
+        Some(v)
+    }
+}
+
+/// Mean of each channel.
+fn mean<I: Iterator<Item = [u8; 3]>>(values: I) -> Option<[u8; 3]> {
+    let (mut sum, mut n) = ([0u64; 3], 0);
+    for value in values {
+        for (sum, v) in sum.iter_mut().zip(value.iter()) {
+            *sum += *v as u64;
+        }
+        n += 1;
+    }
+    if n == 0 {
+        return None;
+    }
+    let avg = |sum: u64| ((sum + n / 2) / n) as u8;
+    Some([avg(sum[0]), avg(sum[1]), avg(sum[2])])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_pixel_access() {
+        let bgr = Mat::<BGR>::new(3, 4, BGR::new(10, 20, 30)).unwrap();
+        assert_eq!(bgr.at(&Point::new(3, 2)).unwrap(), BGR::new(10, 20, 30));
+
+        let hsv = Mat::<HSV>::new(3, 4, HSV::unsafe_new(170, 20, 30)).unwrap();
+        assert_eq!(hsv.at(&Point::new(0, 0)).unwrap(), HSV::unsafe_new(170, 20, 30));
+
+        let gray = Mat::<Gray>::new(3, 4, BGR::new(42, 0, 0)).unwrap();
+        assert_eq!(gray.at(&Point::new(1, 1)).unwrap(), 42);
+    }
+
+    #[test]
+    fn bounds_checked_access() {
+        let gray = Mat::<Gray>::new(3, 4, BGR::new(42, 0, 0)).unwrap();
+        assert!(gray.at(&Point::new(4, 0)).is_err());
+        assert!(gray.at(&Point::new(0, -1)).is_err());
+
+        // clipped at the border
+        assert_eq!(gray.region(&Rect::center(&Point::new(0, 0), 3, 3)).unwrap().len(), 4);
+        assert_eq!(gray.at_avg(&Point::new(3, 2), 5).unwrap(), 42);
+        assert!(gray.at_avg(&Point::new(10, 10), 3).is_err());
+    }
+
+    #[test]
+    fn average_hue_on_the_circle() {
+        let values = [HSV::unsafe_new(179, 100, 100), HSV::unsafe_new(1, 200, 100)];
+        assert_eq!(HSV::average(&values), Some(HSV::unsafe_new(0, 150, 100)));
+        assert_eq!(BGR::average(&[]), None);
+    }
+}
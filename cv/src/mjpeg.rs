@@ -0,0 +1,127 @@
+//!
+//! Tiny MJPEG-over-HTTP server to test network sources without a real camera.
+//!
+//! ```no_run
+//! let frames = vec![cv::imread("frame.png")?];
+//! let server = cv::MjpegServer::spawn("127.0.0.1:0", &frames, 25.0)?;
+//! let capture = cv::VideoCapture::open_url(&server.url())?;
+//! # Ok::<(), cv::Error>(())
+//! ```
+use crate::*;
+use log::{debug, info};
+use opencv::types::{VectorOfint, VectorOfuchar};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+const BOUNDARY: &str = "mjpegframe";
+
+/// MJPEG server which streams the given frames in an endless loop to each client.
+pub struct MjpegServer {
+    addr: SocketAddr,
+}
+
+impl MjpegServer {
+    /// Binds the server to the given address and serves the frames in a background thread.
+    ///
+    /// Use port `0` to let the OS pick a free port - see `MjpegServer::addr`.
+    pub fn spawn(addr: &str, frames: &[Mat<BGR>], fps: f64) -> Result<MjpegServer> {
+        if frames.is_empty() {
+            return Err(Error::UserInput {
+                msg: "mjpeg server needs at least one frame".into(),
+            });
+        }
+
+        let jpegs = frames.iter().map(encode_jpeg).collect::<Result<Vec<_>>>()?;
+        let jpegs = Arc::new(jpegs);
+        let delay = Duration::from_secs_f64(1.0 / fps);
+
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        info!("mjpeg server listen on {}", addr);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().filter_map(|s| s.ok()) {
+                let jpegs = Arc::clone(&jpegs);
+                thread::spawn(move || {
+                    let peer = stream.peer_addr().ok();
+                    if let Err(err) = serve(stream, &jpegs, delay) {
+                        debug!("mjpeg client {:?} disconnected: {}", peer, err);
+                    }
+                });
+            }
+        });
+
+        Ok(MjpegServer { addr })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stream url for `VideoCapture::open_url`.
+    pub fn url(&self) -> String {
+        format!("http://{}/stream.mjpg", self.addr)
+    }
+}
+
+fn encode_jpeg(frame: &Mat<BGR>) -> Result<Vec<u8>> {
+    let mut buf = VectorOfuchar::new();
+    opencv::imgcodecs::imencode(".jpg", frame.unpack(), &mut buf, &VectorOfint::new())?;
+    Ok(buf.to_vec())
+}
+
+fn serve(mut stream: TcpStream, jpegs: &[Vec<u8>], delay: Duration) -> std::io::Result<()> {
+    // consume the request header - the path is ignored
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    write!(
+        stream,
+        "HTTP/1.0 200 OK\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: close\r\n\
+         Content-Type: multipart/x-mixed-replace; boundary={}\r\n\r\n",
+        BOUNDARY
+    )?;
+
+    for jpeg in jpegs.iter().cycle() {
+        write!(
+            stream,
+            "--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+            BOUNDARY,
+            jpeg.len()
+        )?;
+        stream.write_all(jpeg)?;
+        stream.write_all(b"\r\n")?;
+        thread::sleep(delay);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn stream_frames() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join("objects.png");
+        let frame = imread(path).unwrap();
+        let server = MjpegServer::spawn("127.0.0.1:0", &[frame.clone()], 25.0).unwrap();
+
+        let mut capture = VideoCapture::open_url(&server.url()).unwrap();
+        let received = capture.next().expect("frame from mjpeg server");
+        assert_eq!(received.n_cols(), frame.n_cols());
+        assert_eq!(received.n_rows(), frame.n_rows());
+    }
+}
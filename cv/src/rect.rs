@@ -13,6 +13,12 @@ impl Rect {
         Rect { x, y, width, height }
     }
 
+    /// Rect between the two corners - in any order.
+    pub fn from_corners(a: &Point, b: &Point) -> Self {
+        let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+        Rect::new(x, y, (a.x - b.x).abs(), (a.y - b.y).abs())
+    }
+
     pub fn center(p: &Point, width: i32, height: i32) -> Self {
         let x = p.x - (width / 2);
         let y = p.y - (height / 2);
@@ -37,6 +43,34 @@ impl Rect {
         }
     }
 
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Overlapping area of both rects - `None` if they don't overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        if right > x && bottom > y {
+            Some(Rect::new(x, y, right - x, bottom - y))
+        } else {
+            None
+        }
+    }
+
     pub fn contains(&self, pt: Point) -> bool {
         self.unpack().contains(pt.unpack())
     }
@@ -46,3 +80,22 @@ impl Rect {
         self.y = pt.y + self.height / 2;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersection() {
+        let frame = Rect::new(0, 0, 100, 50);
+        assert_eq!(
+            Rect::new(80, -10, 40, 30).intersection(&frame),
+            Some(Rect::new(80, 0, 20, 20))
+        );
+        assert_eq!(
+            Rect::new(10, 10, 5, 5).intersection(&frame),
+            Some(Rect::new(10, 10, 5, 5))
+        );
+        assert_eq!(Rect::new(100, 10, 5, 5).intersection(&frame), None);
+    }
+}
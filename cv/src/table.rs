@@ -0,0 +1,175 @@
+use crate::*;
+use opencv::core::Mat as OpencvMat;
+
+/// Smallest table area in relation to the frame area.
+const MIN_AREA_RATIO: f64 = 0.1;
+
+/// Table candidate from `detect_table`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableQuad {
+    /// left-top, right-top, right-bottom, left-bottom
+    pub corners: [Point; 4],
+    /// how well the quadrilateral matches the shape of the detected outline: 0.0 ..= 1.0
+    ///
+    /// Compares the area of the convex hull of the outline with the area of the
+    /// quadrilateral - it doesn't check how much of the quadrilateral is backed by edges.
+    pub shape_fit: f64,
+}
+
+/// Finds the playing surface of the table in the frame.
+///
+/// Detects the edges, and fits the largest convex quadrilateral
+/// to the outlines of the edges - see `TableQuad::shape_fit`. `None` if no quadrilateral covers
+/// at least 10% of the frame.
+pub fn detect_table(frame: &Mat<BGR>) -> Result<Option<TableQuad>> {
+    let mut gray: Mat<Gray> = frame.convert_color();
+    gray.blur(5);
+
+    let mut edges = OpencvMat::default()?;
+    opencv::imgproc::canny(gray.unpack(), &mut edges, 50., 150., 3, false)?;
+    let mut edges = Mat::<Gray>::pack(edges);
+    // close the gaps in the edges
+    edges.dilate(5);
+
+    let min_area = MIN_AREA_RATIO * (frame.n_cols() * frame.n_rows()) as f64;
+    let mut best: Option<(f64, TableQuad)> = None;
+    for contour in edges.find_contours().iter() {
+        let hull = contour.convex_hull();
+        let hull_area = hull.area();
+        if hull_area < min_area {
+            continue;
+        }
+
+        let quad = hull.approx_poly_dp(0.02 * hull.arc_length(true), true);
+        if quad.len() != 4 || !quad.is_convex() {
+            continue;
+        }
+
+        let quad_area = quad.area();
+        if best.map_or(false, |(area, _)| area >= quad_area) {
+            continue;
+        }
+        let points = quad.points();
+        let corners = order_corners(&[points[0], points[1], points[2], points[3]]);
+        let shape_fit = (1. - (quad_area - hull_area).abs() / hull_area).max(0.);
+        best = Some((quad_area, TableQuad { corners, shape_fit }));
+    }
+    Ok(best.map(|(_, quad)| quad))
+}
+
+/// Orders the corners of a quadrilateral: left-top, right-top, right-bottom, left-bottom.
+///
+/// The corners are sorted clockwise around their center, starting with the
+/// corner nearest to the image origin.
+pub fn order_corners(corners: &[Point; 4]) -> [Point; 4] {
+    let cx = corners.iter().map(|p| p.x as f64).sum::<f64>() / 4.;
+    let cy = corners.iter().map(|p| p.y as f64).sum::<f64>() / 4.;
+    let angle = |p: &Point| (p.y as f64 - cy).atan2(p.x as f64 - cx);
+
+    let mut sorted = *corners;
+    // the y axis points down - ascending angles are clockwise on the screen
+    sorted.sort_by(|a, b| angle(a).partial_cmp(&angle(b)).unwrap_or(std::cmp::Ordering::Equal));
+    let first = (0..4).min_by_key(|i| sorted[*i].x + sorted[*i].y).unwrap_or(0);
+    sorted.rotate_left(first);
+    sorted
+}
+
+/// Checks that the corners (in clockwise or counterclockwise order) form
+/// a convex, non-degenerate quadrilateral.
+pub fn check_quad(corners: &[Point; 4]) -> Result<()> {
+    for (i, a) in corners.iter().enumerate() {
+        if corners[i + 1..].contains(a) {
+            return Err(Error::UserInput {
+                msg: format!("the corner {} was selected twice", a),
+            });
+        }
+    }
+
+    let mut orientation = 0;
+    for i in 0..4 {
+        let (a, b, c) = (corners[i], corners[(i + 1) % 4], corners[(i + 2) % 4]);
+        let cross = (b.x - a.x) as i64 * (c.y - b.y) as i64 - (b.y - a.y) as i64 * (c.x - b.x) as i64;
+        if cross == 0 {
+            return Err(Error::UserInput {
+                msg: format!("the corners {}, {} and {} are on a line", a, b, c),
+            });
+        }
+        if orientation != 0 && cross.signum() != orientation {
+            return Err(Error::UserInput {
+                msg: format!("the corners {:?} don't form a convex quadrilateral", corners),
+            });
+        }
+        orientation = cross.signum();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order_shuffled_corners() {
+        let (lt, rt, rb, lb) = (
+            Point::new(12, 8),
+            Point::new(950, 15),
+            Point::new(940, 540),
+            Point::new(5, 530),
+        );
+        for corners in &[[rb, lt, lb, rt], [lb, rb, rt, lt], [lt, rt, rb, lb], [rt, lb, lt, rb]] {
+            assert_eq!(order_corners(corners), [lt, rt, rb, lb]);
+        }
+    }
+
+    #[test]
+    fn order_rotated_corners() {
+        // rotated by ~30 degrees
+        let (lt, rt, rb, lb) = (
+            Point::new(100, 50),
+            Point::new(273, 150),
+            Point::new(223, 237),
+            Point::new(50, 137),
+        );
+        assert_eq!(order_corners(&[rb, lb, rt, lt]), [lt, rt, rb, lb]);
+    }
+
+    #[test]
+    fn detect_drawn_table() {
+        let mut frame = Mat::<BGR>::new(240, 320, RGB::black()).unwrap();
+        frame.draw_rect(&Rect::new(60, 40, 200, 160), RGB::white(), -1);
+
+        let table = detect_table(&frame).unwrap().expect("table");
+        let expected = [
+            Point::new(60, 40),
+            Point::new(260, 40),
+            Point::new(260, 200),
+            Point::new(60, 200),
+        ];
+        for (corner, expected) in table.corners.iter().zip(&expected) {
+            assert!(corner.dist(expected) < 8., "{} != {}", corner, expected);
+        }
+        assert!(table.shape_fit > 0.9, "shape fit: {}", table.shape_fit);
+    }
+
+    #[test]
+    fn no_table_in_an_empty_frame() {
+        let frame = Mat::<BGR>::new(240, 320, RGB::black()).unwrap();
+        assert_eq!(detect_table(&frame).unwrap(), None);
+    }
+
+    #[test]
+    fn check_quads() {
+        let (lt, rt, rb, lb) = (
+            Point::new(0, 0),
+            Point::new(100, 0),
+            Point::new(100, 50),
+            Point::new(0, 50),
+        );
+        assert!(check_quad(&[lt, rt, rb, lb]).is_ok());
+        assert!(check_quad(&[lt, lb, rb, rt]).is_ok(), "counterclockwise");
+        assert!(check_quad(&[lt, rt, lb, rb]).is_err(), "twisted");
+        assert!(check_quad(&[lt, rt, rt, lb]).is_err(), "duplicate");
+        assert!(check_quad(&[lt, Point::new(50, 0), rt, lb]).is_err(), "collinear");
+        assert!(check_quad(&[lt, rt, Point::new(50, 10), lb]).is_err(), "concave");
+    }
+}
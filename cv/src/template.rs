@@ -0,0 +1,168 @@
+//!
+//! Object detection by template matching (normalised cross-correlation).
+//!
+use crate::*;
+use opencv::{
+    core::{min_max_loc, Mat as OpencvMat, Point as OpencvPoint, Size},
+    imgproc::{match_template, resize, INTER_AREA, INTER_LINEAR, TM_CCOEFF_NORMED},
+};
+use std::path::PathBuf;
+
+/// Best match of a `TemplateMatcher`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemplateMatch {
+    /// area of the (scaled) template in the frame
+    pub rect: Rect,
+    /// normalised correlation: -1.0 ..= 1.0 - 1.0 is a perfect match
+    pub score: f64,
+    /// template scale of the match
+    pub scale: f64,
+}
+
+impl TemplateMatch {
+    pub fn center(&self) -> Point {
+        Point::new(self.rect.x + self.rect.width / 2, self.rect.y + self.rect.height / 2)
+    }
+}
+
+/// Finds a template image in frames - e.g. the pusher cut out from a frame.
+///
+/// The template is matched with the normalised correlation coefficient, so the
+/// score doesn't depend on the brightness. Use `with_scales` when the object size
+/// in the frame varies.
+#[derive(Debug)]
+pub struct TemplateMatcher {
+    template: Mat<BGR>,
+    scales: Vec<f64>,
+}
+
+impl TemplateMatcher {
+    pub fn new(template: Mat<BGR>) -> Result<Self> {
+        if template.is_empty()? {
+            return Err(Error::UserInput {
+                msg: "empty template".into(),
+            });
+        }
+        Ok(Self {
+            template,
+            scales: vec![1.0],
+        })
+    }
+
+    /// Loads the template from an image file.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        Self::new(imread(path)?)
+    }
+
+    /// Matches the template in the given scales - e.g. `&[0.8, 1.0, 1.2]`.
+    pub fn with_scales(mut self, scales: &[f64]) -> Self {
+        let scales: Vec<f64> = scales.iter().copied().filter(|s| *s > 0.).collect();
+        if !scales.is_empty() {
+            self.scales = scales;
+        }
+        self
+    }
+
+    pub fn template(&self) -> &Mat<BGR> {
+        &self.template
+    }
+
+    pub fn scales(&self) -> &[f64] {
+        &self.scales
+    }
+
+    /// Best match over all scales - `None` if the template is larger than the frame in all scales.
+    pub fn find(&self, frame: &Mat<BGR>) -> Result<Option<TemplateMatch>> {
+        let mut best: Option<TemplateMatch> = None;
+        for scale in &self.scales {
+            if let Some(m) = self.find_scaled(frame, *scale)? {
+                if best.map_or(true, |best| m.score > best.score) {
+                    best = Some(m);
+                }
+            }
+        }
+        Ok(best)
+    }
+
+    fn find_scaled(&self, frame: &Mat<BGR>, scale: f64) -> Result<Option<TemplateMatch>> {
+        let width = (self.template.n_cols() as f64 * scale).round() as i32;
+        let height = (self.template.n_rows() as f64 * scale).round() as i32;
+        if width < 1 || height < 1 || width > frame.n_cols() || height > frame.n_rows() {
+            return Ok(None);
+        }
+
+        let mut scaled = OpencvMat::default()?;
+        let template = if (scale - 1.).abs() < f64::EPSILON {
+            self.template.unpack()
+        } else {
+            let interpolation = if scale < 1. { INTER_AREA } else { INTER_LINEAR };
+            resize(
+                self.template.unpack(),
+                &mut scaled,
+                Size::new(width, height),
+                0.,
+                0.,
+                interpolation,
+            )?;
+            &scaled
+        };
+
+        let mut scores = OpencvMat::default()?;
+        match_template(
+            frame.unpack(),
+            template,
+            &mut scores,
+            TM_CCOEFF_NORMED,
+            &OpencvMat::default()?,
+        )?;
+
+        let (mut score, mut location) = (0., OpencvPoint::default());
+        min_max_loc(
+            &scores,
+            &mut 0.,
+            &mut score,
+            &mut OpencvPoint::default(),
+            &mut location,
+            &OpencvMat::default()?,
+        )?;
+        Ok(Some(TemplateMatch {
+            rect: Rect::new(location.x, location.y, width, height),
+            score,
+            scale,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame() -> Mat<BGR> {
+        let mut frame = Mat::<BGR>::new(120, 160, RGB::black()).unwrap();
+        frame.draw_rect(&Rect::new(100, 40, 20, 20), RGB::red(), -1);
+        frame.draw_circle(&Point::new(110, 50), 5, RGB::white(), -1);
+        frame
+    }
+
+    #[test]
+    fn find_template() {
+        let template = frame().roi(Rect::new(95, 35, 30, 30)).unwrap();
+        let matcher = TemplateMatcher::new(template).unwrap();
+
+        let m = matcher.find(&frame()).unwrap().unwrap();
+        assert_eq!(m.rect, Rect::new(95, 35, 30, 30));
+        assert_eq!(m.center(), Point::new(110, 50));
+        assert!(m.score > 0.99);
+    }
+
+    #[test]
+    fn template_larger_than_frame() {
+        let template = Mat::<BGR>::new(20, 20, RGB::red()).unwrap();
+        let matcher = TemplateMatcher::new(template).unwrap().with_scales(&[10.0]);
+        assert_eq!(matcher.find(&frame()).unwrap(), None);
+        assert_eq!(matcher.scales(), &[10.0]);
+    }
+}
@@ -0,0 +1,44 @@
+//! Fixtures for the tests - the tests of other crates enable them with the `testing` feature.
+use crate::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Temporary directory - removed on drop, also when the test panics.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty `airhobot-<name>-<pid>` directory in the temp dir of the os.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("airhobot-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Directory with `n` images (`frame-<index>.png`) - the index is encoded in the pixel value, see `image_index`.
+pub fn image_dir(name: &str, n: u8) -> TempDir {
+    let dir = TempDir::new(name);
+    for i in 0..n {
+        let frame = Mat::<BGR>::new(4, 4, RGB::new(i, i, i)).expect("image");
+        imwrite(dir.path().join(format!("frame-{}.png", i)), &frame).expect("write image");
+    }
+    dir
+}
+
+/// Index of an image from `image_dir`.
+pub fn image_index(frame: &Mat<BGR>) -> u8 {
+    frame.at(&Point::new(0, 0)).expect("pixel").b
+}
@@ -0,0 +1,197 @@
+use crate::*;
+use log::{debug, trace, warn};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+/// Frame with the time when it was captured and the running frame number.
+#[derive(Debug, Clone)]
+pub struct TimestampedFrame {
+    pub mat: Mat<BGR>,
+    pub captured_at: Instant,
+    pub number: u64,
+}
+
+impl TimestampedFrame {
+    /// Stamps the frame with the current time.
+    pub fn now(mat: Mat<BGR>, number: u64) -> Self {
+        TimestampedFrame {
+            mat,
+            captured_at: Instant::now(),
+            number,
+        }
+    }
+}
+
+/// Reads the frames from a `VideoCapture` (or any other frame iterator) in a background thread.
+///
+/// Only the newest frame is kept - if the consumer is slower than the camera,
+/// the older frames are dropped (see `ThreadedCapture::dropped`). This prevents
+/// the backend buffer from handing out stale frames with a growing latency.
+pub struct ThreadedCapture {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    slot: Mutex<Slot>,
+    cond: Condvar,
+    stop: StopSignal,
+    dropped: AtomicU64,
+}
+
+#[derive(Default)]
+struct Slot {
+    frame: Option<TimestampedFrame>,
+    finished: bool,
+}
+
+impl ThreadedCapture {
+    /// Starts the capture thread.
+    pub fn spawn<S>(capture: S) -> ThreadedCapture
+    where
+        S: Iterator<Item = Mat<BGR>> + Send + 'static,
+    {
+        Self::spawn_stoppable(capture, StopSignal::new())
+    }
+
+    /// Starts the capture thread - `drop` stops the given signal, so a blocking capture
+    /// (e.g. the reconnects of a `VideoCapture`, see `VideoCapture::stop_signal`) ends.
+    pub fn spawn_stoppable<S>(mut capture: S, stop: StopSignal) -> ThreadedCapture
+    where
+        S: Iterator<Item = Mat<BGR>> + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            slot: Mutex::new(Slot::default()),
+            cond: Condvar::new(),
+            stop,
+            dropped: AtomicU64::new(0),
+        });
+
+        let handle = thread::spawn({
+            let shared = Arc::clone(&shared);
+            move || {
+                let mut number = 0;
+                while !shared.stop.is_stopped() {
+                    let mat = match capture.next() {
+                        Some(mat) => mat,
+                        None => break,
+                    };
+                    let mut slot = shared.slot.lock().expect("capture slot lock");
+                    let frame = TimestampedFrame::now(mat, number);
+                    if let Some(old) = slot.frame.replace(frame) {
+                        trace!("drop frame {}", old.number);
+                        shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    number += 1;
+                    shared.cond.notify_all();
+                }
+                debug!("capture thread finished after {} frames", number);
+                if let Ok(mut slot) = shared.slot.lock() {
+                    slot.finished = true;
+                }
+                shared.cond.notify_all();
+            }
+        });
+
+        ThreadedCapture {
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    /// Number of frames which were captured but never consumed.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Waits for the next frame - `None` when the capture has finished.
+    pub fn grab(&mut self) -> Option<TimestampedFrame> {
+        let mut slot = self.shared.slot.lock().ok()?;
+        while slot.frame.is_none() && !slot.finished {
+            slot = self.shared.cond.wait(slot).ok()?;
+        }
+        slot.frame.take()
+    }
+}
+
+impl Iterator for ThreadedCapture {
+    type Item = TimestampedFrame;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.grab()
+    }
+}
+
+impl Drop for ThreadedCapture {
+    /// Stops the capture thread and waits for it - a pending read finishes first.
+    fn drop(&mut self) {
+        self.shared.stop.stop();
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                warn!("capture thread panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keep_only_the_latest_frame() {
+        let dir = testing::image_dir("latest-frame", 5);
+        let mut capture = ThreadedCapture::spawn(ImageSequence::open(dir.path()).unwrap());
+
+        // don't consume until all frames are read
+        let started = Instant::now();
+        while capture.dropped() < 4 && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(capture.dropped(), 4);
+
+        let frame = capture.grab().unwrap();
+        assert_eq!(frame.number, 4);
+        assert_eq!(testing::image_index(&frame.mat), 4);
+        assert!(capture.grab().is_none());
+    }
+
+    #[test]
+    fn drop_joins_the_capture_thread() {
+        let dir = testing::image_dir("join", 3);
+        let images = ImageSequence::open(dir.path()).unwrap().looping(true);
+        let capture = ThreadedCapture::spawn(images);
+        let shared = Arc::clone(&capture.shared);
+
+        drop(capture);
+        assert!(shared.slot.lock().unwrap().finished);
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
+    /// Capture which waits for a reconnect - like a `VideoCapture` without a cam.
+    struct Reconnecting(StopSignal);
+
+    impl Iterator for Reconnecting {
+        type Item = Mat<BGR>;
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.sleep(Duration::from_secs(60));
+            None
+        }
+    }
+
+    #[test]
+    fn drop_stops_a_pending_reconnect() {
+        let stop = StopSignal::new();
+        let capture = ThreadedCapture::spawn_stoppable(Reconnecting(stop.clone()), stop);
+
+        let started = Instant::now();
+        drop(capture);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
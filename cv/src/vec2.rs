@@ -0,0 +1,187 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
+};
+
+/// 2D vector / sub-pixel point with `f64` precision.
+///
+/// Use `Vec2::from(point)` and `Vec2::round` to convert from / to the pixel based `Point`.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct Vec2 {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+}
+
+/// Sub-pixel point - same type as `Vec2`.
+pub type Point2f = Vec2;
+
+impl Vec2 {
+    pub fn new(x: f64, y: f64) -> Self {
+        Vec2 { x, y }
+    }
+
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+
+    /// Nearest pixel.
+    pub fn round(&self) -> Point {
+        Point::new(self.x.round() as i32, self.y.round() as i32)
+    }
+
+    pub fn dot(&self, other: &Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// z component of the 3D cross product - positive if `other` is counter-clockwise
+    /// from `self` (with the y axis pointing up).
+    pub fn cross(&self, other: &Vec2) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn dist(&self, other: &Vec2) -> f64 {
+        (*self - *other).norm()
+    }
+
+    /// Unit vector in the same direction - `None` for the zero vector.
+    pub fn normalize(&self) -> Option<Vec2> {
+        let norm = self.norm();
+        if norm > f64::EPSILON {
+            Some(*self / norm)
+        } else {
+            None
+        }
+    }
+
+    /// Rotates by the angle in radians.
+    pub fn rotate(&self, angle: f64) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// Perpendicular vector - rotated by 90 degrees.
+    pub fn perp(&self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+}
+
+impl From<Point> for Vec2 {
+    fn from(p: Point) -> Self {
+        Vec2::new(p.x as f64, p.y as f64)
+    }
+}
+
+impl From<&Point> for Vec2 {
+    fn from(p: &Point) -> Self {
+        Vec2::from(*p)
+    }
+}
+
+impl fmt::Display for Vec2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{x: {:.2}, y: {:.2}}}", self.x, self.y)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Vec2) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, rhs: Vec2) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+    fn mul(self, rhs: f64) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Div<f64> for Vec2 {
+    type Output = Vec2;
+    fn div(self, rhs: f64) -> Vec2 {
+        Vec2::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.dist(&b) < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Vec2::new(1., 2.);
+        let b = Vec2::new(3., -1.);
+        assert_eq!(a + b, Vec2::new(4., 1.));
+        assert_eq!(a - b, Vec2::new(-2., 3.));
+        assert_eq!(a * 2., Vec2::new(2., 4.));
+        assert_eq!(b / 2., Vec2::new(1.5, -0.5));
+        assert_eq!(-a, Vec2::new(-1., -2.));
+        assert_eq!(a.dot(&b), 1.);
+        assert_eq!(a.cross(&b), -7.);
+        assert_eq!(Vec2::new(3., 4.).norm(), 5.);
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+    }
+
+    #[test]
+    fn normalize_and_rotate() {
+        assert_close(Vec2::new(0., 5.).normalize().unwrap(), Vec2::new(0., 1.));
+        assert_eq!(Vec2::default().normalize(), None);
+
+        assert_close(Vec2::new(1., 0.).rotate(FRAC_PI_2), Vec2::new(0., 1.));
+        assert_close(Vec2::new(1., 0.).perp(), Vec2::new(0., 1.));
+    }
+
+    #[test]
+    fn point_conversion() {
+        let p = Point::new(3, -4);
+        assert_eq!(Vec2::from(p), Vec2::new(3., -4.));
+        assert_eq!(Vec2::new(2.5, -0.4).round(), Point::new(3, 0));
+        assert_eq!(Vec2::from(&p).round(), p);
+    }
+}
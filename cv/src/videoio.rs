@@ -1,12 +1,208 @@
 use crate::*;
+use log::{error, info, warn};
 use opencv::videoio::{
     VideoCapture as OpencvVideoCapture, VideoCaptureTrait, VideoWriter as OpencvVideoWriter, VideoWriterTrait, CAP_ANY,
+    CAP_FFMPEG, CAP_GSTREAMER, CAP_PROP_AUTO_EXPOSURE, CAP_PROP_AUTO_WB, CAP_PROP_BUFFERSIZE, CAP_PROP_EXPOSURE,
+    CAP_PROP_FPS, CAP_PROP_FRAME_COUNT, CAP_PROP_FRAME_HEIGHT, CAP_PROP_FRAME_WIDTH, CAP_PROP_GAIN,
+    CAP_PROP_POS_FRAMES, CAP_PROP_POS_MSEC, CAP_PROP_WB_TEMPERATURE,
 };
+use serde::{Deserialize, Serialize};
 use snafu::ensure;
-use std::path::Path;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Where the frames of a `VideoCapture` come from.
+#[derive(Debug, Clone)]
+enum Origin {
+    Device(i32),
+    File(PathBuf),
+    Url(String),
+    GStreamer(String),
+}
+
+impl Origin {
+    fn open(&self) -> Result<OpencvVideoCapture> {
+        let inner = match self {
+            Origin::Device(device_id) => OpencvVideoCapture::new(*device_id, CAP_ANY)?,
+            Origin::File(path) => OpencvVideoCapture::from_file(&path.to_string_lossy(), CAP_ANY)?,
+            Origin::Url(url) => OpencvVideoCapture::from_file(url, CAP_FFMPEG)?,
+            Origin::GStreamer(pipeline) => OpencvVideoCapture::from_file(pipeline, CAP_GSTREAMER)?,
+        };
+        Ok(inner)
+    }
+}
+
+/// Capture properties - see the `VideoCapture` getters / setters.
+///
+/// Which properties are supported depends on the camera and the backend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptureProperty {
+    Width,
+    Height,
+    Fps,
+    Exposure,
+    AutoExposure,
+    Gain,
+    WhiteBalance,
+    AutoWhiteBalance,
+    BufferSize,
+}
+
+impl CaptureProperty {
+    fn unpack(self) -> i32 {
+        match self {
+            CaptureProperty::Width => CAP_PROP_FRAME_WIDTH,
+            CaptureProperty::Height => CAP_PROP_FRAME_HEIGHT,
+            CaptureProperty::Fps => CAP_PROP_FPS,
+            CaptureProperty::Exposure => CAP_PROP_EXPOSURE,
+            CaptureProperty::AutoExposure => CAP_PROP_AUTO_EXPOSURE,
+            CaptureProperty::Gain => CAP_PROP_GAIN,
+            CaptureProperty::WhiteBalance => CAP_PROP_WB_TEMPERATURE,
+            CaptureProperty::AutoWhiteBalance => CAP_PROP_AUTO_WB,
+            CaptureProperty::BufferSize => CAP_PROP_BUFFERSIZE,
+        }
+    }
+}
+
+/// Capture settings which are applied when a `VideoCapture` is opened.
+///
+/// `None` values are left untouched.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CaptureSettings {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub fps: Option<f64>,
+    pub exposure: Option<f64>,
+    pub auto_exposure: Option<bool>,
+    pub gain: Option<f64>,
+    /// white balance temperature in kelvin
+    pub white_balance: Option<f64>,
+    pub auto_white_balance: Option<bool>,
+    pub buffer_size: Option<i32>,
+}
+
+impl CaptureSettings {
+    /// Returns the settings where each value from `other` overrides the value in `self`.
+    pub fn merge(&self, other: &CaptureSettings) -> CaptureSettings {
+        CaptureSettings {
+            width: other.width.or(self.width),
+            height: other.height.or(self.height),
+            fps: other.fps.or(self.fps),
+            exposure: other.exposure.or(self.exposure),
+            auto_exposure: other.auto_exposure.or(self.auto_exposure),
+            gain: other.gain.or(self.gain),
+            white_balance: other.white_balance.or(self.white_balance),
+            auto_white_balance: other.auto_white_balance.or(self.auto_white_balance),
+            buffer_size: other.buffer_size.or(self.buffer_size),
+        }
+    }
+}
+
+impl fmt::Display for CaptureSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn show<T: fmt::Display>(v: &Option<T>) -> String {
+            v.as_ref().map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+        }
+        write!(
+            f,
+            "width: {}, height: {}, fps: {}, exposure: {}, auto-exposure: {}, gain: {}, \
+             white-balance: {}, auto-white-balance: {}, buffer-size: {}",
+            show(&self.width),
+            show(&self.height),
+            show(&self.fps),
+            show(&self.exposure),
+            show(&self.auto_exposure),
+            show(&self.gain),
+            show(&self.white_balance),
+            show(&self.auto_white_balance),
+            show(&self.buffer_size)
+        )
+    }
+}
+
+/// Retry policy for cams and network streams, when a frame can't be read.
+///
+/// The video source is reopened with an exponential backoff between
+/// `min_delay` and `max_delay` (in milliseconds).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub min_delay: u64,
+    pub max_delay: u64,
+    /// give up after the given number of failed attempts in a row - `None` retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl RetryPolicy {
+    /// Delay before the given attempt (starts with 1).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let millis = self.min_delay.saturating_mul(factor).min(self.max_delay);
+        Duration::from_millis(millis)
+    }
+
+    /// `true` if the given attempt (starts with 1) exceeds `max_attempts`.
+    pub fn exhausted(&self, attempt: u32) -> bool {
+        self.max_attempts.map(|max| attempt > max).unwrap_or(false)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            min_delay: 100,
+            max_delay: 5000,
+            max_attempts: None,
+        }
+    }
+}
+
+/// Stops the reconnects of a `VideoCapture` from another thread - see `VideoCapture::stop_signal`.
+#[derive(Debug, Clone, Default)]
+pub struct StopSignal(Arc<AtomicBool>);
+
+impl StopSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Sleeps for the given duration - returns `false` if it was stopped in the meantime.
+    pub fn sleep(&self, dur: Duration) -> bool {
+        let until = Instant::now() + dur;
+        while !self.is_stopped() {
+            let now = Instant::now();
+            if now >= until {
+                return true;
+            }
+            thread::sleep((until - now).min(Duration::from_millis(20)));
+        }
+        false
+    }
+}
 
 pub struct VideoCapture {
     inner: OpencvVideoCapture,
+    origin: Origin,
+    retry: Option<RetryPolicy>,
+    stop: StopSignal,
+    reconnects: u64,
+    settings: Option<CaptureSettings>,
 }
 
 impl VideoCapture {
@@ -17,13 +213,257 @@ impl VideoCapture {
             });
         }
 
-        let inner = OpencvVideoCapture::from_file(&path.to_string_lossy(), opencv::videoio::CAP_ANY)?;
-        Ok(VideoCapture { inner })
+        Self::open(Origin::File(path.to_path_buf()))
     }
 
     pub fn open_device(device_id: i32) -> Result<VideoCapture> {
-        let inner = OpencvVideoCapture::new(device_id, videoio::CAP_ANY)?;
-        Ok(VideoCapture { inner })
+        Self::open(Origin::Device(device_id))
+    }
+
+    /// Opens a network stream (MJPEG over HTTP, RTSP) with the FFMPEG backend.
+    pub fn open_url(url: &str) -> Result<VideoCapture> {
+        Self::open(Origin::Url(url.to_string()))
+    }
+
+    /// Opens a GStreamer pipeline - the pipeline must end with an `appsink`.
+    ///
+    /// ```text
+    /// rtspsrc location=rtsp://10.0.0.2:8554/cam latency=0 ! decodebin ! videoconvert ! appsink
+    /// ```
+    pub fn open_gstreamer(pipeline: &str) -> Result<VideoCapture> {
+        Self::open(Origin::GStreamer(pipeline.to_string()))
+    }
+
+    fn open(origin: Origin) -> Result<VideoCapture> {
+        let inner = origin.open()?;
+        if !inner.is_opened()? {
+            return Err(Error::VideoIO {
+                source: opencv::Error::new(0, format!("unable to open video source: {:?}", origin)),
+            });
+        }
+        Ok(VideoCapture {
+            inner,
+            origin,
+            retry: None,
+            stop: StopSignal::new(),
+            reconnects: 0,
+            settings: None,
+        })
+    }
+
+    /// Applies the given settings and returns the values which are actually in effect.
+    ///
+    /// The settings are applied again after a reconnect.
+    pub fn apply(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings> {
+        self.settings = Some(settings.clone());
+        self.apply_settings(settings)
+    }
+
+    fn apply_settings(&mut self, settings: &CaptureSettings) -> Result<CaptureSettings> {
+        use CaptureProperty::*;
+
+        // the resolution must be set before the fps
+        let values = vec![
+            (Width, settings.width.map(f64::from)),
+            (Height, settings.height.map(f64::from)),
+            (Fps, settings.fps),
+            (AutoExposure, settings.auto_exposure.map(auto_exposure_value)),
+            (Exposure, settings.exposure),
+            (Gain, settings.gain),
+            (
+                AutoWhiteBalance,
+                settings.auto_white_balance.map(|b| if b { 1.0 } else { 0.0 }),
+            ),
+            (WhiteBalance, settings.white_balance),
+            (BufferSize, settings.buffer_size.map(f64::from)),
+        ];
+        for (prop, value) in values.into_iter().filter_map(|(p, v)| v.map(|v| (p, v))) {
+            if !self.set(prop, value)? {
+                warn!("capture property {:?} not supported by {:?}", prop, self.origin);
+            }
+        }
+
+        let effective = self.settings()?;
+        info!("capture settings in effect for {:?}: {}", self.origin, effective);
+        Ok(effective)
+    }
+
+    /// Reads the current values of all capture properties.
+    pub fn settings(&self) -> Result<CaptureSettings> {
+        Ok(CaptureSettings {
+            width: Some(self.width()?),
+            height: Some(self.height()?),
+            fps: Some(self.fps()?),
+            exposure: Some(self.exposure()?),
+            auto_exposure: self.auto_exposure()?,
+            gain: Some(self.gain()?),
+            white_balance: Some(self.white_balance()?),
+            auto_white_balance: Some(self.auto_white_balance()?),
+            buffer_size: Some(self.buffer_size()?),
+        })
+    }
+
+    /// Returns the raw value of the given property - `0.0` if the property is not supported.
+    pub fn get(&self, prop: CaptureProperty) -> Result<f64> {
+        Ok(self.inner.get(prop.unpack())?)
+    }
+
+    /// Sets the raw value of the given property - returns `false` if the property is not supported.
+    pub fn set(&mut self, prop: CaptureProperty, value: f64) -> Result<bool> {
+        Ok(self.inner.set(prop.unpack(), value)?)
+    }
+
+    pub fn width(&self) -> Result<i32> {
+        Ok(self.get(CaptureProperty::Width)? as i32)
+    }
+
+    pub fn set_width(&mut self, width: i32) -> Result<bool> {
+        self.set(CaptureProperty::Width, width.into())
+    }
+
+    pub fn height(&self) -> Result<i32> {
+        Ok(self.get(CaptureProperty::Height)? as i32)
+    }
+
+    pub fn set_height(&mut self, height: i32) -> Result<bool> {
+        self.set(CaptureProperty::Height, height.into())
+    }
+
+    pub fn fps(&self) -> Result<f64> {
+        self.get(CaptureProperty::Fps)
+    }
+
+    pub fn set_fps(&mut self, fps: f64) -> Result<bool> {
+        self.set(CaptureProperty::Fps, fps)
+    }
+
+    pub fn exposure(&self) -> Result<f64> {
+        self.get(CaptureProperty::Exposure)
+    }
+
+    /// Sets the exposure - disable `auto_exposure` first, the unit depends on the backend.
+    pub fn set_exposure(&mut self, exposure: f64) -> Result<bool> {
+        self.set(CaptureProperty::Exposure, exposure)
+    }
+
+    /// `None` if the backend reports an unknown value - `get(CaptureProperty::AutoExposure)` returns the raw value.
+    pub fn auto_exposure(&self) -> Result<Option<bool>> {
+        Ok(auto_exposure_from_value(self.get(CaptureProperty::AutoExposure)?))
+    }
+
+    pub fn set_auto_exposure(&mut self, enabled: bool) -> Result<bool> {
+        self.set(CaptureProperty::AutoExposure, auto_exposure_value(enabled))
+    }
+
+    pub fn gain(&self) -> Result<f64> {
+        self.get(CaptureProperty::Gain)
+    }
+
+    pub fn set_gain(&mut self, gain: f64) -> Result<bool> {
+        self.set(CaptureProperty::Gain, gain)
+    }
+
+    /// White balance temperature in kelvin.
+    pub fn white_balance(&self) -> Result<f64> {
+        self.get(CaptureProperty::WhiteBalance)
+    }
+
+    /// Sets the white balance temperature in kelvin - disable `auto_white_balance` first.
+    pub fn set_white_balance(&mut self, kelvin: f64) -> Result<bool> {
+        self.set(CaptureProperty::WhiteBalance, kelvin)
+    }
+
+    pub fn auto_white_balance(&self) -> Result<bool> {
+        Ok(self.get(CaptureProperty::AutoWhiteBalance)? > 0.5)
+    }
+
+    pub fn set_auto_white_balance(&mut self, enabled: bool) -> Result<bool> {
+        self.set(CaptureProperty::AutoWhiteBalance, if enabled { 1.0 } else { 0.0 })
+    }
+
+    /// Position of the last read frame in a video file.
+    pub fn position(&self) -> Result<Duration> {
+        let millis = self.inner.get(CAP_PROP_POS_MSEC)?;
+        Ok(Duration::from_micros((millis.max(0.0) * 1000.0) as u64))
+    }
+
+    /// Index of the next frame in a video file.
+    pub fn frame_position(&self) -> Result<u64> {
+        Ok(self.inner.get(CAP_PROP_POS_FRAMES)?.max(0.0) as u64)
+    }
+
+    /// Seeks to the frame with the given index in a video file.
+    pub fn set_frame_position(&mut self, n: u64) -> Result<()> {
+        let n = n.min(self.frame_count()?.saturating_sub(1));
+        ensure!(
+            self.inner.set(CAP_PROP_POS_FRAMES, n as f64)?,
+            UserInput {
+                msg: format!("unable to seek to frame {} in {:?}", n, self.origin)
+            }
+        );
+        Ok(())
+    }
+
+    /// Seeks to the given position in a video file.
+    pub fn set_position(&mut self, ts: Duration) -> Result<()> {
+        ensure!(
+            self.inner.set(CAP_PROP_POS_MSEC, ts.as_secs_f64() * 1000.0)?,
+            UserInput {
+                msg: format!("unable to seek to {:?} in {:?}", ts, self.origin)
+            }
+        );
+        Ok(())
+    }
+
+    /// Number of frames in a video file.
+    pub fn frame_count(&self) -> Result<u64> {
+        Ok(self.inner.get(CAP_PROP_FRAME_COUNT)?.max(0.0) as u64)
+    }
+
+    /// Number of frames in the internal buffer of the backend.
+    pub fn buffer_size(&self) -> Result<i32> {
+        Ok(self.get(CaptureProperty::BufferSize)? as i32)
+    }
+
+    pub fn set_buffer_size(&mut self, n: i32) -> Result<bool> {
+        self.set(CaptureProperty::BufferSize, n.into())
+    }
+
+    /// Reconnect with the given policy, when a frame can't be read.
+    ///
+    /// Without this, the `Iterator` impl ends at the first read error.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) {
+        self.retry = policy;
+    }
+
+    /// Signal to stop the reconnects - the `Iterator` impl ends, when it's stopped.
+    pub fn stop_signal(&self) -> StopSignal {
+        self.stop.clone()
+    }
+
+    /// Number of successful reconnects.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    /// Closes and opens the underlying video source again.
+    pub fn reopen(&mut self) -> Result<()> {
+        let inner = self.origin.open()?;
+        if !inner.is_opened()? {
+            return Err(Error::VideoIO {
+                source: opencv::Error::new(0, format!("unable to reopen video source: {:?}", self.origin)),
+            });
+        }
+        self.inner = inner;
+        if let Some(settings) = self.settings.clone() {
+            self.apply_settings(&settings)?;
+        }
+        Ok(())
     }
 
     pub fn grab(&mut self) -> Result<Mat<BGR>> {
@@ -31,16 +471,141 @@ impl VideoCapture {
         self.inner.read(&mut frame)?;
         Ok(Mat::pack(frame))
     }
+
+    fn grab_non_empty(&mut self) -> Option<Mat<BGR>> {
+        let mat = self.grab().ok()?;
+        if mat.is_empty().ok()? {
+            return None;
+        }
+        Some(mat)
+    }
 }
 
 impl Iterator for VideoCapture {
     type Item = Mat<BGR>;
     fn next(&mut self) -> Option<Self::Item> {
-        let mat = self.grab().ok()?;
-        if mat.is_empty().ok()? {
-            return None;
+        let mat = self.grab_non_empty();
+        if mat.is_some() {
+            return mat;
         }
-        return Some(mat);
+
+        let policy = self.retry.clone()?;
+        for attempt in 1.. {
+            if self.stop.is_stopped() {
+                info!("reconnect to {:?} stopped", self.origin);
+                return None;
+            }
+            if policy.exhausted(attempt) {
+                error!(
+                    "unable to read from {:?} - give up after {} attempts",
+                    self.origin,
+                    attempt - 1
+                );
+                return None;
+            }
+
+            let delay = policy.delay(attempt);
+            warn!(
+                "unable to read from {:?} - reconnect attempt {} in {:?}",
+                self.origin, attempt, delay
+            );
+            if !self.stop.sleep(delay) {
+                info!("reconnect to {:?} stopped", self.origin);
+                return None;
+            }
+            match self.reopen() {
+                Ok(()) => {
+                    if let Some(mat) = self.grab_non_empty() {
+                        self.reconnects += 1;
+                        info!("reconnected to {:?} after {} attempts", self.origin, attempt);
+                        return Some(mat);
+                    }
+                }
+                Err(err) => warn!("reconnect attempt {} failed: {}", attempt, err),
+            }
+        }
+        None
+    }
+}
+
+/// the V4L2 backend expects `0.75` for auto and `0.25` for manual exposure
+fn auto_exposure_value(enabled: bool) -> f64 {
+    if enabled {
+        0.75
+    } else {
+        0.25
+    }
+}
+
+/// reads back the backend specific auto exposure value:
+///
+///   - `0.25` / `0.75`: manual / auto - the values which `auto_exposure_value` sets, some backends return them
+///   - V4L2: `1` manual, `2` shutter priority, `3` aperture priority (the auto mode of most webcams)
+///
+/// `0` is ambiguous (V4L2 full auto, but also the value of unsupported properties) and gives `None`.
+fn auto_exposure_from_value(value: f64) -> Option<bool> {
+    let is = |v: f64| (value - v).abs() < 0.01;
+    if is(0.25) || is(1.0) {
+        Some(false)
+    } else if is(0.75) || is(2.0) || is(3.0) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Four character code of a video codec - e.g. `XVID`, `MJPG`, `mp4v` or `H264`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FourCC([u8; 4]);
+
+impl FourCC {
+    pub fn new(code: [u8; 4]) -> Self {
+        FourCC(code)
+    }
+
+    fn unpack(self) -> Result<i32> {
+        let [c1, c2, c3, c4] = self.0;
+        Ok(OpencvVideoWriter::fourcc(c1 as i8, c2 as i8, c3 as i8, c4 as i8)?)
+    }
+}
+
+impl Default for FourCC {
+    fn default() -> Self {
+        FourCC(*b"XVID")
+    }
+}
+
+impl std::str::FromStr for FourCC {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.as_bytes() {
+            [c1, c2, c3, c4] if s.is_ascii() => Ok(FourCC([*c1, *c2, *c3, *c4])),
+            _ => Err(Error::UserInput {
+                msg: format!(
+                    "invalid codec: '{}' - expected four ascii characters like XVID or MJPG",
+                    s
+                ),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for FourCC {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.0))
+    }
+}
+
+impl Serialize for FourCC {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for FourCC {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -51,20 +616,39 @@ pub struct VideoWriter {
 }
 
 impl VideoWriter {
+    /// Opens a video writer with the `XVID` codec.
     pub fn new(path: &Path, fps: f64, width: i32, height: i32) -> Result<VideoWriter> {
+        Self::with_codec(path, FourCC::default(), fps, width, height)
+    }
+
+    pub fn with_codec(path: &Path, codec: FourCC, fps: f64, width: i32, height: i32) -> Result<VideoWriter> {
         let mut inner = OpencvVideoWriter::default()?;
 
         let size = opencv::core::Size::new(width, height);
-        let fourcc = OpencvVideoWriter::fourcc('X' as i8, 'V' as i8, 'I' as i8, 'D' as i8)?;
-        if inner.open(&path.to_string_lossy(), fourcc, fps, size, true)? {
+        if inner.open(&path.to_string_lossy(), codec.unpack()?, fps, size, true)? {
             Ok(VideoWriter { inner, width, height })
         } else {
             Err(Error::VideoIO {
-                source: opencv::Error::new(0, format!("unable to video-writer")),
+                source: opencv::Error::new(
+                    0,
+                    format!(
+                        "unable to open video-writer for {} with codec {}",
+                        path.display(),
+                        codec
+                    ),
+                ),
             })
         }
     }
 
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
     pub fn write(&mut self, frame: &Mat<BGR>) -> Result<()> {
         ensure!(
             frame.n_cols() == self.width,
@@ -88,3 +672,41 @@ impl VideoWriter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_policy_backoff() {
+        let policy = RetryPolicy {
+            min_delay: 100,
+            max_delay: 1000,
+            max_attempts: Some(5),
+        };
+        let delays: Vec<_> = (1..=6).map(|n| policy.delay(n).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+        assert!(!policy.exhausted(5));
+        assert!(policy.exhausted(6));
+        assert!(!RetryPolicy::default().exhausted(u32::max_value()));
+    }
+
+    #[test]
+    fn auto_exposure_read_back() {
+        assert_eq!(auto_exposure_from_value(auto_exposure_value(true)), Some(true));
+        assert_eq!(auto_exposure_from_value(auto_exposure_value(false)), Some(false));
+        // V4L2
+        assert_eq!(auto_exposure_from_value(1.0), Some(false));
+        assert_eq!(auto_exposure_from_value(3.0), Some(true));
+        // unsupported
+        assert_eq!(auto_exposure_from_value(0.0), None);
+    }
+
+    #[test]
+    fn fourcc_from_str() {
+        assert_eq!("MJPG".parse::<FourCC>().unwrap(), FourCC::new(*b"MJPG"));
+        assert_eq!(FourCC::new(*b"mp4v").to_string(), "mp4v");
+        assert!("MJPEG".parse::<FourCC>().is_err());
+        assert!("XVI".parse::<FourCC>().is_err());
+    }
+}